@@ -0,0 +1,39 @@
+//! Kaomoji closing cppgen's messages, swapped for ASCII with `--plain` for
+//! terminals that render them as mojibake.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Switches plain mode on or off, returning the previous setting.
+pub fn set_plain(plain: bool) -> bool {
+    PLAIN.swap(plain, Ordering::Relaxed)
+}
+
+fn pick(fancy: &'static str, plain: &'static str) -> &'static str {
+    if PLAIN.load(Ordering::Relaxed) {
+        plain
+    } else {
+        fancy
+    }
+}
+
+/// Something went wrong.
+pub fn sad() -> &'static str {
+    pick("(｡•́︿•̀｡)", ":(")
+}
+
+/// The project was created.
+pub fn happy() -> &'static str {
+    pick("(｡•̀ᴗ-)✧", ":)")
+}
+
+/// The user typed something invalid.
+pub fn annoyed() -> &'static str {
+    pick("( ｡ •̀ ᴖ •́ ｡)", ">:(")
+}
+
+/// cppgen itself is lost.
+pub fn confused() -> &'static str {
+    pick("( – ⌓ – )", "(-_-)")
+}
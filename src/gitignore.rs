@@ -0,0 +1,124 @@
+pub static C: &str = include_str!("templates/c.gitignore");
+pub static CPP: &str = include_str!("templates/cpp.gitignore");
+
+static CMAKE: &str = include_str!("templates/cmake.gitignore");
+static LINUX: &str = include_str!("templates/linux.gitignore");
+static MACOS: &str = include_str!("templates/macos.gitignore");
+static WINDOWS: &str = include_str!("templates/windows.gitignore");
+
+/// Bundled fragments that can be combined with `--gitignore`.
+pub static FRAGMENTS: &[(&str, &str)] = &[
+    ("c", C),
+    ("cpp", CPP),
+    ("cmake", CMAKE),
+    ("linux", LINUX),
+    ("macos", MACOS),
+    ("windows", WINDOWS),
+];
+
+/// Joins the named fragments into a single `.gitignore`.
+pub fn combine(names: &[String]) -> Result<String, String> {
+    let mut fragments = Vec::new();
+    for name in names {
+        let name = match name.to_lowercase().as_str() {
+            "c++" | "cxx" => "cpp".to_string(),
+            other => other.to_string(),
+        };
+        let Some((_, fragment)) = FRAGMENTS.iter().find(|(known, _)| *known == name) else {
+            let known: Vec<&str> = FRAGMENTS.iter().map(|(known, _)| *known).collect();
+            return Err(format!(
+                "Gitignore: unknown fragment \"{}\" (available: {}).",
+                name,
+                known.join(", ")
+            ));
+        };
+        if !fragments.contains(fragment) {
+            fragments.push(*fragment);
+        }
+    }
+    Ok(dedupe(&fragments.join("\n")))
+}
+
+/// Drops ignore patterns that already appeared earlier in the file. Blocks
+/// left without any pattern also lose their descriptive comments, but the
+/// `### Section ###` headers of every fragment are kept.
+fn dedupe(gitignore: &str) -> String {
+    let mut seen: Vec<&str> = Vec::new();
+    let mut blocks: Vec<Vec<&str>> = Vec::new();
+    for block in gitignore.split("\n\n") {
+        let is_pattern = |line: &&str| !line.trim().is_empty() && !line.trim().starts_with('#');
+        let had_patterns = block.lines().any(|line| is_pattern(&line));
+
+        let mut kept: Vec<&str> = Vec::new();
+        for line in block.lines() {
+            let pattern = line.trim();
+            if is_pattern(&line) {
+                if seen.contains(&pattern) {
+                    continue;
+                }
+                seen.push(pattern);
+            }
+            kept.push(line);
+        }
+
+        if had_patterns && !kept.iter().any(is_pattern) {
+            kept.retain(|line| line.starts_with("###"));
+        }
+        kept.retain(|line| !line.trim().is_empty());
+        if !kept.is_empty() {
+            blocks.push(kept);
+        }
+    }
+
+    let blocks: Vec<String> = blocks.iter().map(|block| block.join("\n")).collect();
+    blocks.join("\n\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(gitignore: &str) -> Vec<&str> {
+        gitignore
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect()
+    }
+
+    #[test]
+    fn overlapping_fragments_list_each_pattern_once() {
+        let names = ["c", "cpp", "cmake"].map(String::from);
+        let combined = combine(&names).unwrap();
+        let combined_patterns = patterns(&combined);
+
+        // The C and C++ fragments share object files, libraries and the like.
+        assert!(patterns(C)
+            .iter()
+            .any(|pattern| patterns(CPP).contains(pattern)));
+        for (index, pattern) in combined_patterns.iter().enumerate() {
+            assert!(
+                !combined_patterns[..index].contains(pattern),
+                "{} is listed twice",
+                pattern
+            );
+        }
+        for pattern in patterns(C)
+            .into_iter()
+            .chain(patterns(CPP))
+            .chain(patterns(CMAKE))
+        {
+            assert!(
+                combined_patterns.contains(&pattern),
+                "{} was dropped",
+                pattern
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_fragments() {
+        let err = combine(&["c".into(), "rust".into()]).unwrap_err();
+        assert!(err.contains("unknown fragment \"rust\""));
+    }
+}
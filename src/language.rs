@@ -0,0 +1,369 @@
+use std::fmt;
+
+use crate::gitignore;
+
+pub struct Language {
+    /// Name used on the command line (case-insensitive).
+    pub name: &'static str,
+    /// Other accepted spellings for `name`.
+    pub aliases: &'static [&'static str],
+    /// Human readable name.
+    pub display: &'static str,
+    pub extension: &'static str,
+    pub header_extension: &'static str,
+    pub gitignore: &'static str,
+    /// CMake language name, as in `project(... CXX)` and `CMAKE_CXX_STANDARD`.
+    pub cmake: &'static str,
+    pub standards: &'static [Standard],
+    /// Standard suggested instead of an outdated one.
+    pub recommended_standard: &'static str,
+    /// Features accepted by `target_compile_features`, minus the `<lang>_std_*` ones.
+    pub compile_features: &'static [&'static str],
+    /// Generated `src/main` for `--main-style classic` and `exit-code`.
+    pub main: &'static str,
+    pub main_exit_code: &'static str,
+    /// Whether headers scope their symbols with a namespace rather than a
+    /// name prefix.
+    pub namespaces: bool,
+    /// Language mode for clang's `-x`.
+    pub clang_mode: &'static str,
+    /// zig subcommand compiling the language (`zig cc`, `zig c++`).
+    pub zig_frontend: &'static str,
+    /// Includes of the `--fuzz` harness, and the linkage libFuzzer needs to
+    /// find `LLVMFuzzerTestOneInput`.
+    pub fuzz_includes: &'static str,
+    pub fuzz_linkage: &'static str,
+}
+
+pub struct Standard {
+    /// Name used on the command line, as in `-std=c++20`.
+    pub name: &'static str,
+    /// Value for `CMAKE_<LANG>_STANDARD`.
+    pub cmake: &'static str,
+    /// First CMake release that understands `cmake`.
+    pub min_cmake: (u32, u32),
+    /// Old enough that picking it earns an advisory.
+    pub outdated: bool,
+}
+
+static MAIN_C: &str = "#include <stdio.h>
+
+int main(void)
+{
+    printf(\"Hello World\");
+    return 0;
+}";
+
+static MAIN_CPP: &str = "#include <iostream>
+
+int main()
+{
+    std::cout << \"Hello World\" << std::endl;
+    return 0;
+}";
+
+static MAIN_C_EXIT_CODE: &str = "#include <stdio.h>
+#include <stdlib.h>
+
+int main(void)
+{
+    if (printf(\"Hello World\\n\") < 0)
+    {
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
+";
+
+static MAIN_CPP_EXIT_CODE: &str = "#include <cstdlib>
+#include <iostream>
+
+int main()
+{
+    std::cout << \"Hello World\" << std::endl;
+    if (!std::cout)
+    {
+        return EXIT_FAILURE;
+    }
+    return EXIT_SUCCESS;
+}
+";
+
+pub static LANGUAGES: &[Language] = &[
+    Language {
+        name: "C",
+        aliases: &[],
+        display: "C",
+        extension: ".c",
+        header_extension: ".h",
+        gitignore: gitignore::C,
+        cmake: "C",
+        recommended_standard: "c17",
+        main: MAIN_C,
+        main_exit_code: MAIN_C_EXIT_CODE,
+        namespaces: false,
+        clang_mode: "c",
+        zig_frontend: "cc",
+        fuzz_includes: "#include <stddef.h>\n#include <stdint.h>",
+        fuzz_linkage: "",
+        compile_features: &[
+            "c_function_prototypes",
+            "c_restrict",
+            "c_static_assert",
+            "c_variadic_macros",
+        ],
+        standards: &[
+            Standard {
+                name: "c89",
+                cmake: "90",
+                min_cmake: (3, 1),
+                outdated: true,
+            },
+            Standard {
+                name: "c99",
+                cmake: "99",
+                min_cmake: (3, 1),
+                outdated: true,
+            },
+            Standard {
+                name: "c11",
+                cmake: "11",
+                min_cmake: (3, 1),
+                outdated: false,
+            },
+            Standard {
+                name: "c17",
+                cmake: "17",
+                min_cmake: (3, 21),
+                outdated: false,
+            },
+            Standard {
+                name: "c23",
+                cmake: "23",
+                min_cmake: (3, 21),
+                outdated: false,
+            },
+        ],
+    },
+    Language {
+        name: "CPP",
+        aliases: &["C++", "CXX"],
+        display: "C++",
+        extension: ".cpp",
+        header_extension: ".hpp",
+        gitignore: gitignore::CPP,
+        cmake: "CXX",
+        recommended_standard: "c++20",
+        main: MAIN_CPP,
+        main_exit_code: MAIN_CPP_EXIT_CODE,
+        namespaces: true,
+        clang_mode: "c++",
+        zig_frontend: "c++",
+        fuzz_includes: "#include <cstddef>\n#include <cstdint>",
+        fuzz_linkage: "extern \"C\" ",
+        compile_features: &[
+            "cxx_aggregate_default_initializers",
+            "cxx_alias_templates",
+            "cxx_alignas",
+            "cxx_alignof",
+            "cxx_attribute_deprecated",
+            "cxx_attributes",
+            "cxx_auto_type",
+            "cxx_binary_literals",
+            "cxx_constexpr",
+            "cxx_contextual_conversions",
+            "cxx_decltype",
+            "cxx_decltype_auto",
+            "cxx_decltype_incomplete_return_types",
+            "cxx_default_function_template_args",
+            "cxx_defaulted_functions",
+            "cxx_defaulted_move_initializers",
+            "cxx_delegating_constructors",
+            "cxx_deleted_functions",
+            "cxx_digit_separators",
+            "cxx_enum_forward_declarations",
+            "cxx_explicit_conversions",
+            "cxx_extended_friend_declarations",
+            "cxx_extern_templates",
+            "cxx_final",
+            "cxx_func_identifier",
+            "cxx_generalized_initializers",
+            "cxx_generic_lambdas",
+            "cxx_inheriting_constructors",
+            "cxx_inline_namespaces",
+            "cxx_lambda_init_captures",
+            "cxx_lambdas",
+            "cxx_local_type_template_args",
+            "cxx_long_long_type",
+            "cxx_noexcept",
+            "cxx_nonstatic_member_init",
+            "cxx_nullptr",
+            "cxx_override",
+            "cxx_range_for",
+            "cxx_raw_string_literals",
+            "cxx_reference_qualified_functions",
+            "cxx_relaxed_constexpr",
+            "cxx_return_type_deduction",
+            "cxx_right_angle_brackets",
+            "cxx_rvalue_references",
+            "cxx_sizeof_member",
+            "cxx_static_assert",
+            "cxx_strong_enums",
+            "cxx_template_template_parameters",
+            "cxx_thread_local",
+            "cxx_trailing_return_types",
+            "cxx_unicode_literals",
+            "cxx_uniform_initialization",
+            "cxx_unrestricted_unions",
+            "cxx_user_literals",
+            "cxx_variable_templates",
+            "cxx_variadic_macros",
+            "cxx_variadic_templates",
+        ],
+        standards: &[
+            Standard {
+                name: "c++98",
+                cmake: "98",
+                min_cmake: (3, 1),
+                outdated: true,
+            },
+            Standard {
+                name: "c++11",
+                cmake: "11",
+                min_cmake: (3, 1),
+                outdated: false,
+            },
+            Standard {
+                name: "c++14",
+                cmake: "14",
+                min_cmake: (3, 1),
+                outdated: false,
+            },
+            Standard {
+                name: "c++17",
+                cmake: "17",
+                min_cmake: (3, 8),
+                outdated: false,
+            },
+            Standard {
+                name: "c++20",
+                cmake: "20",
+                min_cmake: (3, 12),
+                outdated: false,
+            },
+            Standard {
+                name: "c++23",
+                cmake: "23",
+                min_cmake: (3, 20),
+                outdated: false,
+            },
+        ],
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|language| {
+        language.name.eq_ignore_ascii_case(name)
+            || language
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    })
+}
+
+impl Language {
+    pub fn standard(&self, name: &str) -> Option<&'static Standard> {
+        // Every language lives in the static registry, so its standards do too.
+        let standards: &'static [Standard] = self.standards;
+        standards
+            .iter()
+            .find(|standard| standard.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Language {
+    /// Prefix of this language's compile features (`c` or `cxx`).
+    pub fn feature_prefix(&self) -> String {
+        self.cmake.to_lowercase()
+    }
+
+    /// Whether `feature` is a known compile feature, returning the standard it
+    /// stands for in the case of `<lang>_std_*` meta-features.
+    pub fn compile_feature(&self, feature: &str) -> Option<Option<&'static Standard>> {
+        let standards: &'static [Standard] = self.standards;
+        let std_prefix = format!("{}_std_", self.feature_prefix());
+        match feature.strip_prefix(&std_prefix) {
+            Some(version) => standards
+                .iter()
+                .find(|standard| standard.cmake == version)
+                .map(Some),
+            None => self.compile_features.contains(&feature).then_some(None),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} sources, {} headers)",
+            self.display, self.extension, self.header_extension
+        )
+    }
+}
+
+/// An entry of the interactive standard prompt, where `None` leaves the
+/// standard up to the compiler.
+pub struct StandardChoice(pub Option<&'static Standard>);
+
+impl fmt::Display for StandardChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(standard) => write!(f, "{}", standard.name),
+            None => write!(f, "Compiler default"),
+        }
+    }
+}
+
+/// Options offered by the interactive language prompt.
+pub fn language_options() -> Vec<&'static Language> {
+    LANGUAGES.iter().collect()
+}
+
+/// Options offered by the interactive standard prompt, newest first.
+pub fn standard_options(language: &'static Language) -> Vec<StandardChoice> {
+    std::iter::once(StandardChoice(None))
+        .chain(
+            language
+                .standards
+                .iter()
+                .rev()
+                .map(|s| StandardChoice(Some(s))),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offers_every_registry_language() {
+        let offered: Vec<&str> = language_options().iter().map(|l| l.name).collect();
+        let registry: Vec<&str> = LANGUAGES.iter().map(|l| l.name).collect();
+        assert_eq!(offered, registry);
+        assert!(offered.contains(&"C") && offered.contains(&"CPP"));
+    }
+
+    #[test]
+    fn offers_every_standard_newest_first() {
+        let cpp = find("c++").unwrap();
+        let offered: Vec<String> = standard_options(cpp)
+            .iter()
+            .map(|choice| choice.to_string())
+            .collect();
+        assert_eq!(offered[0], "Compiler default");
+        assert_eq!(offered[1], cpp.standards.last().unwrap().name);
+        assert_eq!(offered.len(), cpp.standards.len() + 1);
+    }
+}
@@ -1,13 +1,108 @@
-use std::{io::ErrorKind, process::exit};
+mod face;
+mod gitignore;
+mod language;
+mod marker;
+mod project;
+mod self_check;
+mod update;
 
-use clap::Parser;
+use std::process::exit;
+
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use inquire::validator::Validation;
+use inquire::InquireError;
+use language::{Language, Standard};
+use project::GeneratedProject;
+use serde::{Deserialize, Serialize};
+
+static IWYU_MAPPING: &str = include_str!("templates/iwyu.imp");
+
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum MainStyle {
+    /// Prints and returns 0
+    #[default]
+    Classic,
+    /// Reports failures through EXIT_SUCCESS/EXIT_FAILURE
+    ExitCode,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum ProjectType {
+    /// Program built from the sources in src/
+    #[default]
+    Executable,
+    /// INTERFACE library made only of headers in include/
+    HeaderOnly,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Generator {
+    /// Ninja, driven by build.sh
+    #[default]
+    Ninja,
+    /// Visual Studio 2022, driven by build.bat
+    Vs,
+}
+
+impl Generator {
+    fn cmake_name(self) -> &'static str {
+        match self {
+            Generator::Ninja => "Ninja",
+            Generator::Vs => "Visual Studio 17 2022",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Platform {
+    #[value(name = "x64")]
+    #[serde(rename = "x64")]
+    X64,
+    #[value(name = "Win32")]
+    #[serde(rename = "Win32")]
+    Win32,
+    #[value(name = "ARM64")]
+    #[serde(rename = "ARM64")]
+    Arm64,
+}
+
+impl Platform {
+    fn cmake_name(self) -> &'static str {
+        match self {
+            Platform::X64 => "x64",
+            Platform::Win32 => "Win32",
+            Platform::Arm64 => "ARM64",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Toolchain {
+    /// zig cc / zig c++, handy for cross-compiling
+    Zig,
+}
 
-static C_GIT_IGNORE: &str = include_str!("templates/c.gitignore");
-static CPP_GIT_IGNORE: &str = include_str!("templates/cpp.gitignore");
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// Generate a new project with the options recorded in an existing .cppgen.toml
+    Reproduce {
+        /// Path to the .cppgen.toml, or to the project folder holding it
+        marker: String,
 
-#[derive(Parser, Default)]
-#[command(version = "v1.0.1", about = "Simple CLI to generate C/C++ projects with minimal structure.", author = "R-uan, rpo.lopes@hotmail.com", long_about = None)]
+        /// Name of the new project (defaults to the recorded one)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+}
+
+#[derive(Parser, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+#[command(version = concat!("v", env!("CARGO_PKG_VERSION")), about = "Simple CLI to generate C/C++ projects with minimal structure.", author = "R-uan, rpo.lopes@hotmail.com", long_about = None)]
 struct Args {
     /// Project name (keep in mind your OS limitations)
     #[arg(short, long)]
@@ -16,207 +111,2831 @@ struct Args {
     /// Project programming language (C or CPP)
     #[arg(short, long)]
     language: Option<String>,
+
+    /// Language standard (c89 to c23 for C, c++98 to c++23 for CPP)
+    #[arg(short, long)]
+    standard: Option<String>,
+
+    /// Initialize a git repository and make an initial commit
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    git: bool,
+
+    /// Author of the initial commit as "Name <email>" (defaults to the git config)
+    #[arg(long, value_name = "AUTHOR", requires = "git")]
+    #[serde(skip)]
+    commit_author: Option<String>,
+
+    /// Build a universal (arm64 and x86_64) binary on macOS
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    universal: bool,
+
+    /// Keep an empty, committed "build" folder (build/.gitkeep)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    keep_build_dir: bool,
+
+    /// File with one compiler flag per line, optionally prefixed by "gcc:", "clang:" or "msvc:", copied into the project as warnings.txt (executables only)
+    #[arg(long, value_name = "PATH")]
+    warnings_file: Option<String>,
+
+    /// Append -1, -2, ... to the project name instead of failing when its folder exists
+    #[arg(long)]
+    #[serde(skip)]
+    rename_on_conflict: bool,
+
+    /// Run include-what-you-use on every build (adds a .iwyu.imp mapping file)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    iwyu: bool,
+
+    /// Print every generated file to stdout instead of writing the project
+    #[arg(long, conflicts_with = "git")]
+    #[serde(skip)]
+    stdout: bool,
+
+    /// Look for a newer cppgen release on crates.io
+    #[arg(long)]
+    #[serde(skip)]
+    check_updates: bool,
+
+    /// Never access the network (skips --check-updates)
+    #[arg(long)]
+    #[serde(skip)]
+    offline: bool,
+
+    /// Add a component with its own src/<NAME>/ source and include/<NAME>/ header (repeatable)
+    #[arg(long = "component", value_name = "NAME")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    components: Vec<String>,
+
+    /// Toolchain file passed to CMake by the build script (e.g. vcpkg.cmake)
+    #[arg(long, value_name = "PATH")]
+    toolchain_file: Option<String>,
+
+    /// Required compile feature, e.g. cxx_std_20 or cxx_constexpr (repeatable)
+    #[arg(
+        long = "cxx-feature",
+        visible_alias = "c-feature",
+        value_name = "FEATURE"
+    )]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    compile_features: Vec<String>,
+
+    /// Configure and build the generated project with CMake to make sure it works
+    #[arg(long, conflicts_with = "stdout")]
+    #[serde(skip)]
+    verify: bool,
+
+    /// Delete the generated project if the --verify build fails
+    #[arg(long, requires = "verify")]
+    #[serde(skip)]
+    rollback_on_verify_failure: bool,
+
+    /// Gitignore fragments to combine instead of the language one (c, cpp, cmake, linux, macos, windows)
+    #[arg(long, value_name = "FRAGMENTS", value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    gitignore: Vec<String>,
+
+    /// Flavor of the generated main function
+    #[arg(long, value_enum, default_value_t)]
+    main_style: MainStyle,
+
+    /// Write the project as a .tar.gz archive at PATH instead of a folder
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["stdout", "git", "verify"])]
+    #[serde(skip)]
+    archive: Option<String>,
+
+    /// Kind of project to generate
+    #[arg(short = 't', long = "type", value_enum, default_value_t)]
+    #[serde(rename = "type")]
+    project_type: ProjectType,
+
+    /// Generate a single include/<NAME> header instead of a nested folder (header-only projects)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    single_header: bool,
+
+    /// Newest CMake policy version, emitted as cmake_minimum_required(VERSION <min>...<max>)
+    #[arg(long, value_name = "VERSION")]
+    cmake_policy_max: Option<String>,
+
+    /// List the sources explicitly in CMakeLists.txt instead of globbing src/
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    no_glob: bool,
+
+    /// Do not warn about outdated standards such as c89 or c++98
+    #[arg(long)]
+    #[serde(skip)]
+    allow_old_standard: bool,
+
+    /// Do not make CMake refuse to configure inside the source folder
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    allow_in_source_build: bool,
+
+    /// Message printed after the project is created ({project} and {path} are replaced)
+    #[arg(long, value_name = "TEXT")]
+    #[serde(skip)]
+    post_message: Option<String>,
+
+    /// Bundled template set to generate from (defaults to the newest)
+    #[arg(long, value_name = "VERSION")]
+    #[serde(skip)]
+    template_set: Option<u32>,
+
+    /// Open the files in $EDITOR before they are written (interactive mode only)
+    #[arg(long)]
+    #[serde(skip)]
+    edit: bool,
+
+    /// Hide CMake developer warnings (-Wno-dev) in the build script
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    quiet_cmake: bool,
+
+    /// Track common binary asset files with Git LFS (writes .gitattributes)
+    #[arg(long, requires = "git")]
+    #[serde(skip_serializing_if = "is_false")]
+    lfs: bool,
+
+    /// Add VS Code recommended extensions (.vscode/extensions.json)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    vscode: bool,
+
+    /// Prefix for the functions, macros and include guards of generated C headers (e.g. foo_)
+    #[arg(long, value_name = "PREFIX")]
+    symbol_prefix: Option<String>,
+
+    /// Write a compile_flags.txt so clangd works before the first CMake configure
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    compile_flags_txt: bool,
+
+    /// Link a library (e.g. m, dl, threads), can be repeated
+    #[arg(long = "link", value_name = "LIB")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<String>,
+
+    /// Write a JSON list of the generated files, with sizes and hashes, to PATH
+    #[arg(long, value_name = "PATH", conflicts_with = "stdout")]
+    #[serde(skip)]
+    output_manifest: Option<String>,
+
+    /// Reject non-ASCII project names instead of transliterating them for the CMake target
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    strict_name_ascii: bool,
+
+    /// CMake generator used by the build script
+    #[arg(long, value_enum, default_value_t)]
+    generator: Generator,
+
+    /// Target platform for the Visual Studio generator (-A)
+    #[arg(long, value_enum, ignore_case = true)]
+    platform: Option<Platform>,
+
+    /// Toolset for the Visual Studio generator (-T), e.g. v143 or ClangCL
+    #[arg(long, value_name = "TOOLSET")]
+    toolset: Option<String>,
+
+    /// Add a libFuzzer harness in fuzz/, built with -DBUILD_FUZZERS=ON (needs Clang)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    fuzz: bool,
+
+    /// Use plain ASCII instead of kaomoji in messages
+    #[arg(long)]
+    #[serde(skip)]
+    plain: bool,
+
+    /// Render every language, standard and feature in memory and report broken templates
+    #[arg(long, hide = true)]
+    #[serde(skip)]
+    self_check: bool,
+
+    /// Compiler frontend set in CMakeLists.txt
+    #[arg(long, value_enum, conflicts_with = "toolchain_file")]
+    toolchain: Option<Toolchain>,
+
+    /// Leave matching files out of the globbed sources, e.g. src/gen/** or *_win.c (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    excludes: Vec<String>,
+
+    /// Copy a .cmake file into cmake/ and include() it from CMakeLists.txt (repeatable)
+    #[arg(long = "cmake-include", value_name = "PATH")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    cmake_includes: Vec<String>,
+
+    /// Describe the CMake targets and what they link in targets.dot (Graphviz)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    dep_graph: bool,
+
+    #[command(subcommand)]
+    #[serde(skip)]
+    command: Option<Command>,
 }
 
-struct ValidArgs<'a> {
+struct ValidArgs {
+    /// Name of the project folder, kept as given.
     name: String,
-    language: String,
-    extension: String,
-    gitignore: &'a str,
-    cmake: String,
+    /// ASCII form of `name` used for the CMake target, binary and headers.
+    target: String,
+    language: &'static Language,
+    standard: Option<&'static Standard>,
+    gitignore: String,
+    git: bool,
+    commit_author: Option<(String, String)>,
+    universal: bool,
+    keep_build_dir: bool,
+    warnings: Vec<String>,
+    /// Contents of the `--warnings-file`, copied into the project.
+    warnings_copy: Option<String>,
+    iwyu: bool,
+    stdout: bool,
+    components: Vec<String>,
+    toolchain_file: Option<String>,
+    compile_features: Vec<String>,
+    /// Standards implied by `<lang>_std_*` compile features.
+    feature_standards: Vec<&'static Standard>,
+    verify: bool,
+    rollback_on_verify_failure: bool,
+    main_style: MainStyle,
+    archive: Option<String>,
+    project_type: ProjectType,
+    single_header: bool,
+    cmake_policy_max: Option<(u32, u32)>,
+    no_glob: bool,
+    allow_in_source_build: bool,
+    post_message: Option<String>,
+    template_set: u32,
+    /// Options recorded in the `.cppgen.toml` marker.
+    recorded: Args,
+    edit: bool,
+    quiet_cmake: bool,
+    lfs: bool,
+    vscode: bool,
+    /// Stands in for namespaces in C headers, empty when not requested.
+    symbol_prefix: String,
+    compile_flags_txt: bool,
+    /// Libraries to link, with aliases resolved.
+    links: Vec<Link>,
+    /// Packages to find for the aliased libraries.
+    link_packages: Vec<&'static str>,
+    output_manifest: Option<String>,
+    generator: Generator,
+    platform: Option<Platform>,
+    toolset: Option<String>,
+    fuzz: bool,
+    toolchain: Option<Toolchain>,
+    /// `--exclude` globs translated to CMake regexes.
+    excludes: Vec<String>,
+    /// File names and contents of the `--cmake-include` files.
+    cmake_includes: Vec<(String, String)>,
+    dep_graph: bool,
 }
 
 impl Args {
-    fn new(name: Option<String>, language: Option<String>) -> Self {
-        Args { name, language }
-    }
+    fn into_valid(self) -> ValidArgs {
+        let mut recorded = self.clone();
+        let mut name = self.name.unwrap();
+        if self.rename_on_conflict {
+            name = free_project_name(&name).unwrap_or_else(|| {
+                eprintln!(
+                    "Could not find a free folder name for \"{}\" after {} attempts {}",
+                    name,
+                    MAX_RENAME_ATTEMPTS,
+                    face::sad()
+                );
+                exit(1);
+            });
+        }
+        if self.strict_name_ascii && !name.is_ascii() {
+            eprintln!(
+                "Project name: \"{}\" contains non-ASCII characters (try \"{}\").",
+                name,
+                target_name(&name)
+            );
+            exit(1);
+        }
+        let target = target_name(&name);
+        let requested = self.language.unwrap();
+        let Some(language) = language::find(&requested) else {
+            let names: Vec<String> = language::LANGUAGES
+                .iter()
+                .map(|language| match language.aliases.first() {
+                    Some(alias) => format!("{} ({})", language.name, alias),
+                    None => language.name.to_string(),
+                })
+                .collect();
+            eprintln!(
+                "Language: \"{}\" is not available (available: {}).",
+                requested,
+                names.join(", ")
+            );
+            exit(1);
+        };
+        let standard = self.standard.map(|name| {
+            language.standard(&name).unwrap_or_else(|| {
+                let names: Vec<&str> = language.standards.iter().map(|s| s.name).collect();
+                eprintln!(
+                    "Standard: \"{}\" is not a {} standard (available: {}).",
+                    name,
+                    language.display,
+                    names.join(", ")
+                );
+                exit(1);
+            })
+        });
+        if let Some(advisory) =
+            standard.and_then(|standard| old_standard_advisory(language, standard))
+        {
+            if !self.allow_old_standard {
+                eprintln!("{}", advisory);
+            }
+        }
+
+        let template_set = self
+            .template_set
+            .unwrap_or_else(marker::latest_template_set);
+        if !marker::TEMPLATE_SETS.contains(&template_set) {
+            let sets: Vec<String> = marker::TEMPLATE_SETS.iter().map(u32::to_string).collect();
+            eprintln!(
+                "Template set: {} is not bundled with this cppgen (available: {}).",
+                template_set,
+                sets.join(", ")
+            );
+            exit(1);
+        }
+
+        for (index, component) in self.components.iter().enumerate() {
+            if !is_identifier(component) {
+                eprintln!(
+                    "Component: \"{}\" must only contain letters, digits and underscores, and not start with a digit.",
+                    component
+                );
+                exit(1);
+            }
+            if self.components[..index].contains(component) {
+                eprintln!("Component: \"{}\" was given more than once.", component);
+                exit(1);
+            }
+        }
+
+        let commit_author = self.commit_author.map(|author| {
+            parse_author(&author).unwrap_or_else(|| {
+                eprintln!("Commit author must be in the \"Name <email>\" format.");
+                exit(1);
+            })
+        });
+
+        let universal = self.universal && std::env::consts::OS == "macos";
+        if self.universal && !universal {
+            eprintln!("Warning: --universal only applies to macOS, ignoring it.");
+        }
+
+        if self.project_type == ProjectType::HeaderOnly && self.warnings_file.is_some() {
+            eprintln!("--warnings-file only applies to executables, a header-only library would force the flags on its users.");
+            exit(1);
+        }
+        let (warnings, warnings_copy) = match &self.warnings_file {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                    eprintln!("Could not read warnings file \"{}\": {}", path, err);
+                    exit(1);
+                });
+                let flags = parse_warnings(path, &contents, language.cmake).unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    exit(1);
+                });
+                (flags, Some(contents))
+            }
+            None => (Vec::new(), None),
+        };
+
+        if self.iwyu && !find_program("include-what-you-use") && !find_program("iwyu") {
+            eprintln!(
+                "Warning: include-what-you-use was not found in PATH, the build will skip it."
+            );
+        }
+
+        if self.fuzz && !find_program("clang") && !find_program("clang++") {
+            eprintln!(
+                "Warning: clang was not found in PATH, libFuzzer targets only build with Clang."
+            );
+        }
+
+        if self.generator == Generator::Vs && self.toolchain.is_some() {
+            eprintln!("--toolchain does not apply to the Visual Studio generator, which ignores CMAKE_<LANG>_COMPILER (use --toolset instead).");
+            exit(1);
+        }
+        if self.toolchain == Some(Toolchain::Zig) && !find_program("zig") {
+            eprintln!("Warning: zig was not found in PATH, CMake will fail to find the compiler until it is installed.");
+        }
 
-    fn to_real(self) -> ValidArgs<'static> {
-        let name = self.name.unwrap();
-        let language = self.language.unwrap().to_owned().to_uppercase();
-        let (extension, gitignore, cmake) = match language.as_str() {
-            "C" => (".c".into(), &C_GIT_IGNORE, "C".into()).into(),
-            "CPP" => (".cpp".into(), &CPP_GIT_IGNORE, "CXX".into()),
-            _ => (".c".into(), &C_GIT_IGNORE, "C".into()),
+        if self
+            .toolchain_file
+            .as_ref()
+            .is_some_and(|path| path.trim().is_empty())
+        {
+            eprintln!("Toolchain file path cannot be empty.");
+            exit(1);
+        }
+
+        let mut feature_standards = Vec::new();
+        for feature in &self.compile_features {
+            match language.compile_feature(feature) {
+                Some(Some(standard)) => feature_standards.push(standard),
+                Some(None) => {}
+                None => {
+                    eprintln!(
+                        "Compile feature: \"{}\" is not a known {} feature (e.g. {}_std_{}).",
+                        feature,
+                        language.display,
+                        language.feature_prefix(),
+                        language.standards[language.standards.len() - 1].cmake
+                    );
+                    exit(1);
+                }
+            }
+        }
+
+        let gitignore = if self.gitignore.is_empty() {
+            language.gitignore.to_string()
+        } else {
+            gitignore::combine(&self.gitignore).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                exit(1);
+            })
         };
 
-        ValidArgs {
+        if self.single_header && self.project_type != ProjectType::HeaderOnly {
+            eprintln!("--single-header only applies to header-only projects (--type header-only).");
+            exit(1);
+        }
+        if self.project_type == ProjectType::HeaderOnly && self.no_glob {
+            eprintln!("--no-glob lists sources, header-only projects have none.");
+            exit(1);
+        }
+        if self.project_type == ProjectType::HeaderOnly && !self.components.is_empty() {
+            eprintln!("Components need sources, they are not available for header-only projects.");
+            exit(1);
+        }
+
+        let symbol_prefix = self.symbol_prefix.unwrap_or_default();
+        if !symbol_prefix.is_empty() {
+            if language.namespaces {
+                eprintln!(
+                    "--symbol-prefix is for C, {} headers already use namespaces.",
+                    language.display
+                );
+                exit(1);
+            }
+            if !is_identifier(&symbol_prefix) {
+                eprintln!(
+                    "Symbol prefix: \"{}\" must only contain letters, digits and underscores, and not start with a digit.",
+                    symbol_prefix
+                );
+                exit(1);
+            }
+        }
+
+        let mut links = Vec::new();
+        let mut link_packages = Vec::new();
+        for lib in &self.links {
+            if let Some((_, target, package)) =
+                LINK_ALIASES.iter().find(|(alias, _, _)| alias == lib)
+            {
+                // `threads` and `pthread` both resolve to Threads::Threads.
+                if !links.iter().any(|link: &Link| link.cmake == *target) {
+                    links.push(Link {
+                        name: lib.clone(),
+                        cmake: target.to_string(),
+                    });
+                }
+                if let Some(package) = package {
+                    if !link_packages.contains(package) {
+                        link_packages.push(*package);
+                    }
+                }
+            } else if is_library_name(lib) {
+                links.push(Link {
+                    name: lib.clone(),
+                    cmake: lib.clone(),
+                });
+            } else {
+                eprintln!(
+                    "Link: \"{}\" is not a valid library name (letters, digits and _ - . + : only).",
+                    lib
+                );
+                exit(1);
+            }
+        }
+
+        if self.generator != Generator::Vs && (self.platform.is_some() || self.toolset.is_some()) {
+            eprintln!("--platform and --toolset only apply to the Visual Studio generator (--generator vs).");
+            exit(1);
+        }
+        if let Some(toolset) = &self.toolset {
+            if toolset.is_empty()
+                || !toolset
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_.,=".contains(c))
+            {
+                eprintln!(
+                    "Toolset: \"{}\" is not a valid toolset (e.g. v143, ClangCL or v143,host=x64).",
+                    toolset
+                );
+                exit(1);
+            }
+        }
+
+        if !self.excludes.is_empty()
+            && (self.no_glob || self.project_type == ProjectType::HeaderOnly)
+        {
+            eprintln!("--exclude filters the globbed sources, it does not apply with --no-glob or header-only projects.");
+            exit(1);
+        }
+        let excludes = self
+            .excludes
+            .iter()
+            .map(|glob| {
+                glob_to_regex(glob).unwrap_or_else(|| {
+                    eprintln!(
+                        "Exclude: \"{}\" must be a relative path pattern using only letters, digits, / . _ - + and the * ** ? wildcards.",
+                        glob
+                    );
+                    exit(1);
+                })
+            })
+            .collect();
+
+        let mut cmake_includes: Vec<(String, String)> = Vec::new();
+        for path in &self.cmake_includes {
+            let path = std::path::Path::new(path);
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if path.extension().is_none_or(|ext| ext != "cmake") {
+                eprintln!(
+                    "CMake include: \"{}\" must be a .cmake file.",
+                    path.display()
+                );
+                exit(1);
+            }
+            if file_name.contains(['"', '\\', '$']) {
+                eprintln!(
+                    "CMake include: \"{}\" cannot be quoted in CMakeLists.txt, rename it.",
+                    file_name
+                );
+                exit(1);
+            }
+            if cmake_includes.iter().any(|(name, _)| *name == file_name) {
+                eprintln!(
+                    "CMake include: more than one file is named \"{}\".",
+                    file_name
+                );
+                exit(1);
+            }
+            let contents = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                eprintln!(
+                    "CMake include: could not read \"{}\": {}",
+                    path.display(),
+                    err
+                );
+                exit(1);
+            });
+            cmake_includes.push((file_name, contents));
+        }
+
+        let cmake_policy_max = self.cmake_policy_max.map(|version| {
+            parse_cmake_version(&version).unwrap_or_else(|| {
+                eprintln!(
+                    "CMake policy version: \"{}\" must look like <major>.<minor> (e.g. 3.28).",
+                    version
+                );
+                exit(1);
+            })
+        });
+
+        recorded.name = Some(name.clone());
+        recorded.language = Some(language.name.to_string());
+        // The files are copied into the project and recorded relative to it,
+        // which is where `reproduce` resolves them from.
+        recorded.warnings_file = warnings_copy.is_some().then(|| WARNINGS_FILE.to_string());
+        recorded.cmake_includes = cmake_includes
+            .iter()
+            .map(|(file_name, _)| format!("{}/{}", CMAKE_DIR, file_name))
+            .collect();
+
+        let valid = ValidArgs {
             name,
+            target,
             language,
-            extension,
+            standard,
             gitignore,
-            cmake,
+            git: self.git,
+            commit_author,
+            universal,
+            keep_build_dir: self.keep_build_dir,
+            warnings,
+            warnings_copy,
+            iwyu: self.iwyu,
+            stdout: self.stdout,
+            components: self.components,
+            toolchain_file: self.toolchain_file,
+            compile_features: self.compile_features,
+            feature_standards,
+            verify: self.verify,
+            rollback_on_verify_failure: self.rollback_on_verify_failure,
+            main_style: self.main_style,
+            archive: self.archive,
+            project_type: self.project_type,
+            single_header: self.single_header,
+            cmake_policy_max,
+            no_glob: self.no_glob,
+            allow_in_source_build: self.allow_in_source_build,
+            post_message: self.post_message,
+            template_set,
+            recorded,
+            edit: self.edit,
+            quiet_cmake: self.quiet_cmake,
+            lfs: self.lfs,
+            vscode: self.vscode,
+            symbol_prefix,
+            compile_flags_txt: self.compile_flags_txt,
+            links,
+            link_packages,
+            output_manifest: self.output_manifest,
+            generator: self.generator,
+            platform: self.platform,
+            toolset: self.toolset,
+            fuzz: self.fuzz,
+            toolchain: self.toolchain,
+            excludes,
+            cmake_includes,
+            dep_graph: self.dep_graph,
+        };
+
+        if let Some((max_major, max_minor)) = valid.cmake_policy_max {
+            let (major, minor) = cmake_minimum_version(&valid);
+            if (max_major, max_minor) < (major, minor) {
+                eprintln!(
+                    "CMake policy version: {}.{} is older than the required minimum {}.{}.",
+                    max_major, max_minor, major, minor
+                );
+                exit(1);
+            }
         }
+
+        valid
+    }
+}
+
+/// Warning suggesting a modern standard when `standard` is an outdated one.
+fn old_standard_advisory(language: &Language, standard: &Standard) -> Option<String> {
+    standard.outdated.then(|| {
+        format!(
+            "Warning: {} is outdated, consider a modern standard such as {} (--allow-old-standard silences this).",
+            standard.name, language.recommended_standard
+        )
+    })
+}
+
+/// Parses a `major.minor[.patch]` CMake version, keeping major and minor.
+fn parse_cmake_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    match parts.next() {
+        Some(patch) if patch.parse::<u32>().is_err() => None,
+        _ if parts.next().is_some() => None,
+        _ => Some((major, minor)),
     }
 }
 
 fn main() {
-    let args = Args::parse();
-    let valid_args: ValidArgs;
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    face::set_plain(args.plain);
+    if args.self_check {
+        exit(self_check::run());
+    }
+    let check_updates = args.check_updates && !args.offline;
 
-    if args.language.is_some() && args.name.is_some() {
-        let v_args = args.to_real();
-        if v_args.language != "C" && v_args.language != "CPP" {
-            eprintln!("Language: Only C and CPP (C++) available.");
-            exit(1);
+    let valid_args = if let Some(Command::Reproduce { marker, name }) = args.command.clone() {
+        let given: Vec<String> = matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(|id| id.to_string())
+            .collect();
+        reproduce_args(&marker, name, args, &given).into_valid()
+    } else if args.language.is_some() && args.name.is_some() {
+        if args.edit {
+            eprintln!("Warning: --edit only applies to interactive mode, ignoring it.");
+        }
+        Args {
+            edit: false,
+            ..args
         }
-        valid_args = v_args;
+        .into_valid()
     } else {
-        valid_args = interactive_prompt().to_real();
+        interactive_prompt(args).into_valid()
+    };
+
+    let project = create_project(&valid_args);
+
+    if valid_args.git {
+        init_repository(&valid_args, &project.root);
     }
 
-    create_project(&valid_args);
-}
+    if valid_args.verify {
+        if let Err(err) = verify_project(&valid_args) {
+            verification_failed(&valid_args, &project, &err);
+            exit(1);
+        }
+    }
 
-fn interactive_prompt() -> Args {
-    let name = inquire::Text::new("Project name")
-        .with_validator(|name: &str| {
-            if name.trim().len() == 0 {
-                return Ok(Validation::Invalid(
-                    "Project name necessary ( ｡ •̀ ᴖ •́ ｡)".into(),
-                ));
-            } else {
-                if !match std::env::consts::OS {
-                    "linux" | "freebsd" => !name.contains('/'),
-                    "windows" => name
-                        .chars()
-                        .any(|c| ['<', '>', ':', '"', '/', '\\', '|', '?', '*'].contains(&c)),
-                    "macos" => !name.contains("/") && !name.contains(":"),
-                    _ => {
-                        return Ok(Validation::Invalid(
-                            "Could not identify the OS ( – ⌓ – )".into(),
-                        ))
-                    }
-                } {
-                    return Ok(Validation::Invalid(
-                        "Invalid character in name ( ｡ •̀ ᴖ •́ ｡)".into(),
-                    ));
-                };
-            }
-            return Ok(Validation::Valid);
-        })
-        .prompt();
+    if let Some(manifest) = &valid_args.output_manifest {
+        if let Err(err) = std::fs::write(manifest, project.manifest()) {
+            eprintln!("Could not write \"{}\" {}: {}", manifest, face::sad(), err);
+            exit(1);
+        }
+    }
 
-    let options: Vec<&str> = vec!["C", "CPP"];
-    let language = inquire::Select::new("Language: ", options)
-        .with_help_message("For the creation of CMake file and the main script")
-        .prompt();
+    if !valid_args.stdout {
+        print_summary(&valid_args);
+    }
 
-    Args::new(
-        Some(name.unwrap().to_string()),
-        Some(language.unwrap().to_string()),
-    )
+    if check_updates {
+        update::check_for_updates();
+    }
 }
 
-fn create_project(args: &ValidArgs) {
-    let cmake = &args.cmake;
-    let project_name = &args.name;
-    let language = &args.language;
-    let gitignore = &args.gitignore;
-    let extension = &args.extension;
+/// Reports a failed `--verify`, removing the project again with
+/// `--rollback-on-verify-failure`.
+fn verification_failed(args: &ValidArgs, project: &GeneratedProject, err: &str) {
+    eprintln!("Verification failed {}: {}", face::sad(), err);
+    if args.rollback_on_verify_failure {
+        project.remove();
+        eprintln!("Removed \"{}\".", args.name);
+    }
+}
 
-    if let Err(err) = std::fs::create_dir(&project_name) {
-        if err.kind() == ErrorKind::AlreadyExists {
-            eprintln!("\"{}\" folder already exists (｡•́︿•̀｡)", &project_name);
-            exit(1);
-        } else {
-            eprintln!(
-                "Could not create project folder (｡•́︿•̀｡): {}",
-                err.to_string()
-            );
-            exit(1);
-        }
+/// Options for a new project generated like the one `path` records. The
+/// options `given` on the command line (by id) replace the recorded ones,
+/// and the ones that are never recorded, such as `--verify`, come from `cli`.
+fn reproduce_args(path: &str, name: Option<String>, cli: Args, given: &[String]) -> Args {
+    let marker = marker::Marker::read(std::path::Path::new(path)).unwrap_or_else(|err| {
+        eprintln!("{} {}", err, face::sad());
+        exit(1);
+    });
+
+    // Recorded paths are relative to the folder holding the marker.
+    let marker_path = std::path::Path::new(path);
+    let project_dir = if marker_path.is_dir() {
+        marker_path
     } else {
-        let _ = std::fs::create_dir(format!("./{}/src", &project_name))
-            .expect("Could not create \"src\" folder (｡•́︿•̀｡)");
-        let _ = std::fs::create_dir(format!("./{}/build", &project_name))
-            .expect("Could not create \"build\" folder (｡•́︿•̀｡)");
-        let _ = std::fs::create_dir(format!("./{}/include", &project_name))
-            .expect("Could not create \"include\" folder (｡•́︿•̀｡)");
-
-        let undo_all = || {
-            let _ = std::fs::remove_dir_all(format!("./{}", project_name));
-            exit(1);
+        marker_path.parent().unwrap_or(std::path::Path::new(""))
+    };
+    let resolve = |recorded: String| project_dir.join(recorded).display().to_string();
+    let mut recorded = marker.options;
+    recorded.warnings_file = recorded.warnings_file.map(resolve);
+    recorded.cmake_includes = recorded.cmake_includes.into_iter().map(resolve).collect();
+
+    let mut options =
+        toml::Table::try_from(&recorded).expect("marker options are always serializable");
+    let cli_options = toml::Table::try_from(&cli).expect("options are always serializable");
+    for id in given {
+        let key = match id.as_str() {
+            "project_type" => "type",
+            id => id,
         };
+        if let Some(value) = cli_options.get(key) {
+            options.insert(key.to_string(), value.clone());
+        }
+    }
+    let options: Args = options.try_into().unwrap_or_else(|err| {
+        eprintln!(
+            "\"{}\" records invalid options {}: {}",
+            path,
+            face::sad(),
+            err
+        );
+        exit(1);
+    });
+    if options.name.is_none() || options.language.is_none() {
+        eprintln!(
+            "\"{}\" does not record a project name and language {}",
+            path,
+            face::sad()
+        );
+        exit(1);
+    }
 
-        let c_make = format!(
-            "cmake_minimum_required(VERSION 3.11)
+    Args {
+        name: name.or(options.name),
+        template_set: cli.template_set.or(Some(marker.template_set)),
+        commit_author: cli.commit_author,
+        rename_on_conflict: cli.rename_on_conflict,
+        stdout: cli.stdout,
+        check_updates: cli.check_updates,
+        offline: cli.offline,
+        verify: cli.verify,
+        rollback_on_verify_failure: cli.rollback_on_verify_failure,
+        archive: cli.archive,
+        allow_old_standard: cli.allow_old_standard,
+        post_message: cli.post_message,
+        output_manifest: cli.output_manifest,
+        plain: cli.plain,
+        ..options
+    }
+}
 
-set(PROJECT_NAME {})
+fn print_summary(args: &ValidArgs) {
+    let path = match &args.archive {
+        Some(archive) => std::path::PathBuf::from(archive),
+        None => std::path::PathBuf::from(&args.name),
+    };
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
 
-project(${{PROJECT_NAME}} {})
+    match &args.archive {
+        Some(_) => println!(
+            "\"{}\" archived at {} {}",
+            args.name,
+            path.display(),
+            face::happy()
+        ),
+        None => println!(
+            "\"{}\" created at {} {}",
+            args.name,
+            path.display(),
+            face::happy()
+        ),
+    }
 
-file(GLOB_RECURSE SOURCES \"src/*.cpp\")
+    if let Some(message) = &args.post_message {
+        println!("{}", post_message(message, &args.name, &path));
+    }
+}
 
-include_directories(${{CMAKE_SOURCE_DIR}}/include)
+/// Fills the `{project}` and `{path}` placeholders of a `--post-message`.
+fn post_message(message: &str, name: &str, path: &std::path::Path) -> String {
+    message
+        .replace("{project}", name)
+        .replace("{path}", &path.display().to_string())
+}
 
-add_executable(${{PROJECT_NAME}} ${{SOURCES}})",
-            project_name, cmake
-        );
+const MAX_RENAME_ATTEMPTS: usize = 100;
 
-        let main_c = format!(
-            "#include <stdio.h>
+/// Returns `name`, or the first `name-N` whose folder does not exist yet.
+fn free_project_name(name: &str) -> Option<String> {
+    if !std::path::Path::new(name).exists() {
+        return Some(name.to_string());
+    }
 
-int main(void)
-{{
-    printf(\"Hello World\");
-    return 0;
-}}"
-        );
+    let renamed = (1..=MAX_RENAME_ATTEMPTS)
+        .map(|attempt| format!("{}-{}", name, attempt))
+        .find(|candidate| !std::path::Path::new(candidate).exists())?;
+    eprintln!(
+        "\"{}\" already exists, creating \"{}\" instead.",
+        name, renamed
+    );
+    Some(renamed)
+}
 
-        let main_cpp = format!(
-            "#include <iostream>
+/// Whether `name` is usable as a C/C++ identifier.
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
 
-int main()
-{{
-    std::cout << \"Hello World\" << std::endl;
-    return 0;
-}}"
-        );
+/// A library given to `--link`.
+struct Link {
+    /// As given on the command line, e.g. `dl`.
+    name: String,
+    /// As passed to `target_link_libraries`, e.g. `${CMAKE_DL_LIBS}`.
+    cmake: String,
+}
 
-        if let Err(err) = std::fs::write(format!("./{}/CMakeLists.txt", &project_name), c_make) {
-            eprintln!(
-                "Could not create CMakeLists.txt script (｡•́︿•̀｡): {}",
-                err.to_string()
-            );
-            undo_all();
-        };
+/// `--link` shorthands: the library target to link and the package providing it.
+const LINK_ALIASES: &[(&str, &str, Option<&str>)] = &[
+    ("threads", "Threads::Threads", Some("Threads")),
+    ("pthread", "Threads::Threads", Some("Threads")),
+    ("dl", "${CMAKE_DL_LIBS}", None),
+];
 
-        if let Err(err) = std::fs::write(
-            format!("./{}/src/main{}", project_name, extension),
-            match language.as_str() {
-                "C" => main_c,
-                "CPP" => main_cpp,
-                _ => main_c,
-            },
-        ) {
-            eprintln!(
-                "Could not create main script file (｡•́︿•̀｡): {}",
-                err.to_string()
-            );
-            undo_all();
-        };
+/// Whether `name` can be passed as is to `target_link_libraries` (a plain
+/// library like `m` or an imported target like `ZLIB::ZLIB`).
+fn is_library_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.+:".contains(c))
+}
 
-        if let Err(err) = std::fs::write(
-            format!("./{}/build.sh", &project_name),
-            format!(
-                "cmake -S . -B build -G \"Ninja\"
-cmake --build build
-./build/{}
-            ",
-                project_name
-            ),
-        ) {
-            eprintln!(
-                "Could not create build script (｡•́︿•̀｡): {}",
-                err.to_string()
-            );
-            undo_all();
+/// Translates an `--exclude` glob (relative to the project) into a regex for
+/// `list(FILTER ... EXCLUDE REGEX)` over sources globbed `RELATIVE` to the
+/// project, escaped for a quoted CMake string. As in .gitignore, a pattern
+/// without `/` matches file names at any depth.
+fn glob_to_regex(glob: &str) -> Option<String> {
+    let glob = glob.strip_prefix("./").unwrap_or(glob);
+    if glob.is_empty()
+        || glob.starts_with('/')
+        || glob.split('/').any(|part| part == "..")
+        || !glob
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+*?".contains(c))
+    {
+        return None;
+    }
+
+    let mut regex = String::from(if glob.contains('/') { "^" } else { "(^|/)" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '.' | '+' => {
+                regex.push_str("\\\\");
+                regex.push(c);
+            }
+            c => regex.push(c),
+        }
+    }
+    regex.push('$');
+    Some(regex)
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Splits a `Name <email>` identity into its name and email parts.
+fn parse_author(author: &str) -> Option<(String, String)> {
+    let (name, rest) = author.split_once('<')?;
+    let email = rest.strip_suffix('>')?;
+    let (name, email) = (name.trim(), email.trim());
+    if name.is_empty() || email.is_empty() || email.contains(['<', '>']) {
+        return None;
+    }
+    Some((name.to_string(), email.to_string()))
+}
+
+/// Reads compiler flags from the `contents` of the warnings file at `path`.
+/// Flags annotated with a compiler (`gcc: -Wlogical-op`) are wrapped in a
+/// generator expression so they only reach that compiler.
+fn parse_warnings(path: &str, contents: &str, cmake_language: &str) -> Result<Vec<String>, String> {
+    let mut flags = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (compiler_ids, line) = match line.split_once(':') {
+            Some((compiler, rest)) if compiler.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let ids: &[&str] = match compiler.to_lowercase().as_str() {
+                    "gcc" => &["GNU"],
+                    "clang" => &["Clang", "AppleClang"],
+                    "msvc" => &["MSVC"],
+                    _ => {
+                        return Err(format!(
+                            "{}:{}: unknown compiler \"{}\" (expected gcc, clang or msvc)",
+                            path,
+                            number + 1,
+                            compiler
+                        ))
+                    }
+                };
+                (Some(ids), rest)
+            }
+            _ => (None, line),
         };
 
-        if let Err(err) = std::fs::write(format!("./{}/.gitignore", project_name), gitignore) {
-            eprintln!("Could not create .gitignore (｡•́︿•̀｡): {}", err.to_string());
-            undo_all();
+        for flag in line.split_whitespace() {
+            flags.push(match compiler_ids {
+                Some(ids) => {
+                    let conditions: Vec<String> = ids
+                        .iter()
+                        .map(|id| format!("$<{}_COMPILER_ID:{}>", cmake_language, id))
+                        .collect();
+                    if conditions.len() == 1 {
+                        format!("$<{}:{}>", conditions[0], flag)
+                    } else {
+                        format!("$<$<OR:{}>:{}>", conditions.join(","), flag)
+                    }
+                }
+                None => flag.to_string(),
+            });
+        }
+    }
+
+    Ok(flags)
+}
+
+/// Checks a project name against the characters the current OS forbids in
+/// folder names.
+fn validate_name(name: &str) -> Validation {
+    if name.trim().is_empty() {
+        return Validation::Invalid(format!("Project name necessary {}", face::annoyed()).into());
+    }
+
+    let valid = match std::env::consts::OS {
+        "linux" | "freebsd" => !name.contains('/'),
+        "windows" => !name
+            .chars()
+            .any(|c| ['<', '>', ':', '"', '/', '\\', '|', '?', '*'].contains(&c)),
+        "macos" => !name.contains('/') && !name.contains(':'),
+        _ => {
+            return Validation::Invalid(
+                format!("Could not identify the OS {}", face::confused()).into(),
+            )
+        }
+    };
+    if !valid {
+        return Validation::Invalid(
+            format!("Invalid character in name {}", face::annoyed()).into(),
+        );
+    }
+    Validation::Valid
+}
+
+/// Name suggested by the interactive prompt: the basename of `dir`, as long as
+/// it is a valid project name.
+fn default_project_name(dir: &std::path::Path) -> Option<String> {
+    let name = dir.file_name()?.to_str()?;
+    matches!(validate_name(name), Validation::Valid).then(|| name.to_string())
+}
+
+/// Unwraps a prompt answer, leaving quietly when the user cancels it with
+/// Esc or Ctrl-C.
+fn answer<T>(result: Result<T, InquireError>) -> T {
+    result.unwrap_or_else(|err| {
+        let (message, code) = prompt_failure(&err);
+        eprintln!("{}", message);
+        exit(code);
+    })
+}
+
+/// Message and exit code for a prompt that got no answer: 130, as for
+/// SIGINT, when the user cancelled it.
+fn prompt_failure(err: &InquireError) -> (String, i32) {
+    match err {
+        InquireError::OperationCanceled | InquireError::OperationInterrupted => {
+            (format!("Cancelled {}", face::sad()), 130)
+        }
+        err => (
+            format!("Could not read the answer {}: {}", face::sad(), err),
+            1,
+        ),
+    }
+}
+
+fn interactive_prompt(args: Args) -> Args {
+    let default_name = std::env::current_dir()
+        .ok()
+        .and_then(|dir| default_project_name(&dir))
+        .unwrap_or_default();
+    let name = inquire::Text::new("Project name")
+        .with_initial_value(&default_name)
+        .with_validator(|name: &str| Ok(validate_name(name)))
+        .prompt();
+    let name = answer(name);
+
+    let language = inquire::Select::new("Language: ", language::language_options())
+        .with_help_message("For the creation of CMake file and the main script (type to filter)")
+        .prompt();
+    let language: &Language = answer(language);
+
+    let standard = inquire::Select::new("Standard: ", language::standard_options(language))
+        .with_help_message("Set as CMAKE_<LANG>_STANDARD (type to filter)")
+        .with_page_size(8)
+        .prompt();
+    let standard = answer(standard);
+
+    Args {
+        name: Some(name),
+        language: Some(language.name.to_string()),
+        standard: standard.0.map(|standard| standard.name.to_string()),
+        ..args
+    }
+}
+
+fn create_project(args: &ValidArgs) -> GeneratedProject {
+    let mut project = generate_project(args);
+    if args.edit {
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+        if let Err(err) = project.edit_with(&editor) {
+            eprintln!("{} {}", err, face::sad());
+            exit(1);
+        }
+    }
+
+    if args.stdout {
+        print!("{}", project.listing());
+        return project;
+    }
+
+    if let Some(archive) = &args.archive {
+        if let Err(err) = project.write_archive(archive) {
+            eprintln!("{}", err);
+            exit(1);
+        }
+        return project;
+    }
+
+    if let Err(err) = project.write() {
+        eprintln!("{}", err);
+        exit(1);
+    }
+    project
+}
+
+fn generate_project(args: &ValidArgs) -> GeneratedProject {
+    let project_name = &args.name;
+    let mut project = GeneratedProject::new(project_name);
+
+    if args.project_type != ProjectType::HeaderOnly {
+        project.dir("src");
+    }
+    if args.keep_build_dir {
+        project.file("build/.gitkeep", "");
+    } else {
+        project.dir("build");
+    }
+    project.dir(INCLUDE_DIR);
+
+    let targets = targets(args);
+    project.file("CMakeLists.txt", cmake_lists(args, &targets));
+    match args.project_type {
+        ProjectType::Executable => project.file(
+            main_source(args),
+            match args.main_style {
+                MainStyle::Classic => args.language.main,
+                MainStyle::ExitCode => args.language.main_exit_code,
+            },
+        ),
+        ProjectType::HeaderOnly => header_only_headers(args, &mut project),
+    }
+    match args.generator {
+        Generator::Ninja => project.file("build.sh", build_script(args)),
+        Generator::Vs => project.file("build.bat", build_script(args)),
+    }
+    project.file(".gitignore", gitignore(args));
+    if args.iwyu {
+        project.file(".iwyu.imp", IWYU_MAPPING);
+    }
+    if args.lfs {
+        project.file(".gitattributes", lfs_attributes());
+    }
+    if args.vscode {
+        project.file(".vscode/extensions.json", vscode_extensions(args));
+    }
+    if args.fuzz {
+        project.file(fuzz_harness_path(args), fuzz_harness(args));
+        project.dir(FUZZ_CORPUS_DIR);
+    }
+    if let Some(contents) = &args.warnings_copy {
+        project.file(WARNINGS_FILE, contents.as_str());
+    }
+    for (file_name, contents) in &args.cmake_includes {
+        project.file(format!("{}/{}", CMAKE_DIR, file_name), contents.as_str());
+    }
+    if args.dep_graph {
+        project.file("targets.dot", dependency_graph(&targets));
+    }
+    if args.compile_flags_txt {
+        project.file("compile_flags.txt", compile_flags(args));
+    }
+    project.file(
+        marker::FILE_NAME,
+        marker::Marker::new(args.template_set, args.recorded.clone()).render(),
+    );
+
+    for component in &args.components {
+        let header = format!(
+            "{}/{}{}",
+            component, component, args.language.header_extension
+        );
+        project.file(
+            include_path(&header),
+            component_header(args, component, &header),
+        );
+        project.file(
+            component_source_path(args, component),
+            component_source(args, component, &header),
+        );
+    }
+
+    // Git does not track empty folders, so keep the intended layout alive on
+    // clone. The build folder only ever holds output and is left alone.
+    let empty_dirs: Vec<_> = project
+        .empty_dirs()
+        .filter(|dir| !dir.starts_with("build"))
+        .cloned()
+        .collect();
+    for dir in empty_dirs {
+        project.file(dir.join(".gitkeep"), "");
+    }
+
+    project
+}
+
+/// Folder holding the public headers, and the only include directory of the
+/// generated target.
+const INCLUDE_DIR: &str = "include";
+
+/// Location in the project of a header that sources include as `header`.
+fn include_path(header: &str) -> String {
+    format!("{}/{}", INCLUDE_DIR, header)
+}
+
+fn main_source(args: &ValidArgs) -> String {
+    format!("src/main{}", args.language.extension)
+}
+
+fn component_source_path(args: &ValidArgs, component: &str) -> String {
+    format!("src/{}/{}{}", component, component, args.language.extension)
+}
+
+/// Source files compiled into the executable, relative to the project root.
+fn source_files(args: &ValidArgs) -> Vec<String> {
+    std::iter::once(main_source(args))
+        .chain(
+            args.components
+                .iter()
+                .map(|component| component_source_path(args, component)),
+        )
+        .collect()
+}
+
+/// CMake target name for a project: non-ASCII letters are transliterated
+/// (`café` becomes `cafe`) and anything CMake does not accept in a target
+/// name becomes `_`.
+fn target_name(name: &str) -> String {
+    deunicode::deunicode(name)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "_.+-".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Turns a project name into a C/C++ identifier (`my-lib` becomes `my_lib`).
+fn identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !identifier.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+/// Public headers of a header-only project: `include/<name>/<name>` plus a
+/// version header, or everything amalgamated into `include/<name>` with
+/// `--single-header`.
+fn header_only_headers(args: &ValidArgs, project: &mut GeneratedProject) {
+    let ident = identifier(&args.target);
+    // C symbols, macros included, take the --symbol-prefix instead of the name.
+    let symbol_prefix = if args.symbol_prefix.is_empty() {
+        format!("{}_", ident)
+    } else {
+        args.symbol_prefix.clone()
+    };
+    let version = format!(
+        "#define {prefix}VERSION_MAJOR 0\n#define {prefix}VERSION_MINOR 1\n#define {prefix}VERSION_PATCH 0",
+        prefix = symbol_prefix.to_uppercase()
+    );
+    let api = if args.language.namespaces {
+        format!(
+            "namespace {}
+{{
+constexpr int add(int a, int b)
+{{
+    return a + b;
+}}
+
+inline const char *greeting()
+{{
+    return \"Hello World\";
+}}
+}}",
+            ident
+        )
+    } else {
+        format!(
+            "static inline int {prefix}add(int a, int b)
+{{
+return a + b;
+}}
+
+static inline const char *{prefix}greeting(void)
+{{
+return \"Hello World\";
+}}",
+            prefix = symbol_prefix
+        )
+    };
+    let header = |path: &str, body: &str| {
+        let guard = include_guard(args, path);
+        format!(
+            "#ifndef {guard}\n#define {guard}\n\n{}\n\n#endif\n",
+            body,
+            guard = guard
+        )
+    };
+
+    if args.single_header {
+        let path = format!("{}{}", args.target, args.language.header_extension);
+        let body = format!("{}\n\n{}", version, api);
+        project.file(include_path(&path), header(&path, &body));
+    } else {
+        let version_path = format!("{}/version{}", args.target, args.language.header_extension);
+        let path = format!(
+            "{}/{}{}",
+            args.target, args.target, args.language.header_extension
+        );
+        let body = format!("#include \"{}\"\n\n{}", version_path, api);
+        project.file(include_path(&path), header(&path, &body));
+        project.file(include_path(&version_path), header(&version_path, &version));
+    }
+}
+
+/// Include guard macro for a header at `path` (relative to `include/`).
+fn include_guard(args: &ValidArgs, path: &str) -> String {
+    let guard: String = path
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    args.symbol_prefix.to_ascii_uppercase() + &guard
+}
+
+fn component_header(args: &ValidArgs, component: &str, header: &str) -> String {
+    let guard = include_guard(args, header);
+    let declaration = if args.language.namespaces {
+        format!("namespace {}\n{{\nvoid hello();\n}}", component)
+    } else {
+        format!("void {}{}_hello(void);", args.symbol_prefix, component)
+    };
+    format!(
+        "#ifndef {}\n#define {}\n\n{}\n\n#endif\n",
+        guard, guard, declaration
+    )
+}
+
+fn component_source(args: &ValidArgs, component: &str, header: &str) -> String {
+    if args.language.namespaces {
+        format!(
+            "#include \"{}\"
+
+#include <iostream>
+
+namespace {}
+{{
+void hello()
+{{
+    std::cout << \"Hello from {}\" << std::endl;
+}}
+}}
+",
+            header, component, component
+        )
+    } else {
+        format!(
+            "#include \"{}\"
+
+#include <stdio.h>
+
+void {}{}_hello(void)
+{{
+    printf(\"Hello from {}\\n\");
+}}
+",
+            header, args.symbol_prefix, component, component
+        )
+    }
+}
+
+fn build_script(args: &ValidArgs) -> String {
+    let mut configure = format!("cmake -S . -B build -G \"{}\"", args.generator.cmake_name());
+    if let Some(platform) = args.platform {
+        configure.push_str(&format!(" -A {}", platform.cmake_name()));
+    }
+    if let Some(toolset) = &args.toolset {
+        configure.push_str(&format!(" -T {}", toolset));
+    }
+    if let Some(toolchain_file) = &args.toolchain_file {
+        configure.push_str(&format!(" -DCMAKE_TOOLCHAIN_FILE=\"{}\"", toolchain_file));
+    }
+    if args.quiet_cmake {
+        configure.push_str(" -Wno-dev");
+    }
+
+    match (args.generator, args.project_type) {
+        (Generator::Ninja, ProjectType::Executable) => format!(
+            "{}\ncmake --build build\n./build/{}\n",
+            configure, args.target
+        ),
+        (Generator::Ninja, ProjectType::HeaderOnly) => {
+            format!("{}\ncmake --build build\n", configure)
+        }
+        // Visual Studio is a multi-config generator, binaries land in build\<config>.
+        (Generator::Vs, ProjectType::Executable) => format!(
+            "@echo off\r\n{}\r\ncmake --build build --config Debug\r\nbuild\\Debug\\{}.exe\r\n",
+            configure, args.target
+        ),
+        (Generator::Vs, ProjectType::HeaderOnly) => format!(
+            "@echo off\r\n{}\r\ncmake --build build --config Debug\r\n",
+            configure
+        ),
+    }
+}
+
+/// Extensions VS Code suggests to whoever opens the project.
+const VSCODE_RECOMMENDATIONS: &[&str] = &["ms-vscode.cpptools", "ms-vscode.cmake-tools"];
+
+/// Recommended extensions, plus the ones the chosen features rely on.
+fn vscode_recommendations(args: &ValidArgs) -> Vec<&'static str> {
+    let mut recommendations = VSCODE_RECOMMENDATIONS.to_vec();
+    if args.compile_flags_txt || args.iwyu {
+        // Reads compile_flags.txt and flags unused includes.
+        recommendations.push("llvm-vs-code-extensions.vscode-clangd");
+    }
+    if let Some(Toolchain::Zig) = args.toolchain {
+        recommendations.push("ziglang.vscode-zig");
+    }
+    recommendations
+}
+
+fn vscode_extensions(args: &ValidArgs) -> String {
+    let recommendations: Vec<String> = vscode_recommendations(args)
+        .iter()
+        .map(|extension| format!("        \"{}\"", extension))
+        .collect();
+    format!(
+        "{{\n    \"recommendations\": [\n{}\n    ]\n}}\n",
+        recommendations.join(",\n")
+    )
+}
+
+/// Flags clangd applies to every file when there is no compile_commands.json,
+/// one per line.
+fn compile_flags(args: &ValidArgs) -> String {
+    let mut flags = vec![format!("-x{}", args.language.clang_mode)];
+    if let Some(standard) = args.standard {
+        flags.push(format!("-std={}", standard.name));
+    }
+    flags.push(format!("-I{}", INCLUDE_DIR));
+    flags.join("\n") + "\n"
+}
+
+/// Copy of the `--warnings-file`, kept for `reproduce`.
+const WARNINGS_FILE: &str = "warnings.txt";
+
+/// Folder holding the `--cmake-include` files.
+const CMAKE_DIR: &str = "cmake";
+
+/// Seed inputs libFuzzer starts mutating from (`./fuzzing fuzz/corpus`).
+const FUZZ_CORPUS_DIR: &str = "fuzz/corpus";
+
+fn fuzz_harness_path(args: &ValidArgs) -> String {
+    format!("fuzz/fuzz_target{}", args.language.extension)
+}
+
+fn fuzz_harness(args: &ValidArgs) -> String {
+    format!(
+        "{}
+
+/* Called by libFuzzer with every generated input, feed it to the code under test. */
+{}int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size)
+{{
+    (void)data;
+    (void)size;
+    return 0;
+}}
+",
+        args.language.fuzz_includes, args.language.fuzz_linkage
+    )
+}
+
+/// Binary asset extensions stored with Git LFS by `--lfs`.
+const LFS_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "tga", "psd", "hdr", "wav", "mp3", "ogg", "flac", "mp4",
+    "ttf", "otf", "fbx", "glb", "blend", "zip",
+];
+
+fn lfs_attributes() -> String {
+    LFS_EXTENSIONS
+        .iter()
+        .map(|extension| format!("*.{} filter=lfs diff=lfs merge=lfs -text\n", extension))
+        .collect()
+}
+
+fn gitignore(args: &ValidArgs) -> String {
+    let mut gitignore = args.gitignore.clone();
+    if args.keep_build_dir {
+        // Ignore the build output but not the placeholder that keeps the folder.
+        gitignore.push_str("\n# Build folder\nbuild/*\n!build/.gitkeep\n");
+    }
+    gitignore
+}
+
+/// Oldest CMake able to configure the generated project.
+fn cmake_minimum_version(args: &ValidArgs) -> (u32, u32) {
+    let mut version = (3, 11);
+    if args.fuzz {
+        // The fuzzing target uses target_link_options.
+        version = version.max((3, 13));
+    }
+    for standard in args.standard.iter().chain(&args.feature_standards) {
+        version = version.max(standard.min_cmake);
+    }
+    version
+}
+
+fn cmake_lists(args: &ValidArgs, targets: &[Target]) -> String {
+    let (major, minor) = cmake_minimum_version(args);
+    let policy_max = match args.cmake_policy_max {
+        Some((max_major, max_minor)) => format!("...{}.{}", max_major, max_minor),
+        None => String::new(),
+    };
+    let mut sections = vec![
+        format!(
+            "cmake_minimum_required(VERSION {}.{}{})",
+            major, minor, policy_max
+        ),
+        format!("set(PROJECT_NAME {})", args.target),
+    ];
+
+    if args.universal {
+        sections.push("set(CMAKE_OSX_ARCHITECTURES \"arm64;x86_64\" CACHE STRING \"\")".into());
+    }
+
+    if !args.allow_in_source_build {
+        sections.push(
+            "if(CMAKE_SOURCE_DIR STREQUAL CMAKE_BINARY_DIR)
+    message(FATAL_ERROR \"In-source builds are not allowed, configure with a build folder instead (cmake -B build)\")
+endif()"
+                .into(),
+        );
+    }
+
+    if let Some(Toolchain::Zig) = args.toolchain {
+        // Must be set before project() enables the language.
+        sections.push(format!(
+            "# zig as the compiler, cross-compile by adding a target: -DCMAKE_{lang}_FLAGS=\"-target aarch64-linux-gnu\"
+set(CMAKE_{lang}_COMPILER \"zig;{}\")",
+            args.language.zig_frontend,
+            lang = args.language.cmake
+        ));
+    }
+
+    sections.push(format!(
+        "project(${{PROJECT_NAME}} LANGUAGES {})",
+        args.language.cmake
+    ));
+
+    if let Some(standard) = args.standard {
+        sections.push(format!(
+            "set(CMAKE_{lang}_STANDARD {})\nset(CMAKE_{lang}_STANDARD_REQUIRED ON)",
+            standard.cmake,
+            lang = args.language.cmake
+        ));
+        // Fail at configure time rather than with a deep compile error.
+        sections.push(format!(
+            "if(NOT \"{feature}_std_{}\" IN_LIST CMAKE_{lang}_COMPILE_FEATURES)
+    message(FATAL_ERROR \"${{CMAKE_{lang}_COMPILER_ID}} ${{CMAKE_{lang}_COMPILER_VERSION}} does not support {}\")
+endif()",
+            standard.cmake,
+            standard.name,
+            feature = args.language.cmake.to_lowercase(),
+            lang = args.language.cmake
+        ));
+    }
+
+    if args.iwyu {
+        sections.push(format!(
+            "find_program(IWYU_PATH NAMES include-what-you-use iwyu)
+if(IWYU_PATH)
+    set(CMAKE_{}_INCLUDE_WHAT_YOU_USE
+        ${{IWYU_PATH}} -Xiwyu --mapping_file=${{CMAKE_SOURCE_DIR}}/.iwyu.imp)
+else()
+    message(WARNING \"include-what-you-use not found, skipping it\")
+endif()",
+            args.language.cmake
+        ));
+    }
+
+    if !args.cmake_includes.is_empty() {
+        sections.push(
+            args.cmake_includes
+                .iter()
+                .map(|(file_name, _)| {
+                    format!(
+                        "include(\"${{CMAKE_CURRENT_SOURCE_DIR}}/{}/{}\")",
+                        CMAKE_DIR, file_name
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    for package in &args.link_packages {
+        sections.push(format!("find_package({} REQUIRED)", package));
+    }
+
+    // Usage requirements of an INTERFACE library reach its consumers instead.
+    let scope = match args.project_type {
+        ProjectType::Executable => {
+            if args.no_glob {
+                sections.push(format!(
+                    "# New source files must be added to this list by hand.
+add_executable(${{PROJECT_NAME}}
+    {}
+)",
+                    source_files(args).join("\n    ")
+                ));
+            } else {
+                // Globbed relative to the project when filtered, so that the
+                // folders above it never match an exclude.
+                let relative = if args.excludes.is_empty() {
+                    ""
+                } else {
+                    " RELATIVE \"${CMAKE_CURRENT_SOURCE_DIR}\""
+                };
+                sections.push(format!(
+                    "file(GLOB_RECURSE SOURCES{} \"src/*{}\")",
+                    relative, args.language.extension
+                ));
+                if !args.excludes.is_empty() {
+                    sections.push(
+                        args.excludes
+                            .iter()
+                            .map(|regex| {
+                                format!("list(FILTER SOURCES EXCLUDE REGEX \"{}\")", regex)
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                }
+                sections.push("add_executable(${PROJECT_NAME} ${SOURCES})".into());
+            }
+            "PRIVATE"
+        }
+        ProjectType::HeaderOnly => {
+            sections.push("add_library(${PROJECT_NAME} INTERFACE)".into());
+            "INTERFACE"
+        }
+    };
+
+    // Headers are included relative to this folder (`#include "net/net.hpp"`),
+    // never to one of its subfolders.
+    sections.push(format!(
+        "target_include_directories(${{PROJECT_NAME}} {} ${{CMAKE_CURRENT_SOURCE_DIR}}/{})",
+        scope, INCLUDE_DIR
+    ));
+
+    if !args.compile_features.is_empty() {
+        sections.push(format!(
+            "target_compile_features(${{PROJECT_NAME}} {} {})",
+            scope,
+            args.compile_features.join(" ")
+        ));
+    }
+
+    // Only ever PRIVATE: warnings are not a usage requirement.
+    if !args.warnings.is_empty() {
+        sections.push(format!(
+            "target_compile_options(${{PROJECT_NAME}} PRIVATE\n    {}\n)",
+            args.warnings.join("\n    ")
+        ));
+    }
+
+    let main = &targets[0];
+    if !main.links.is_empty() {
+        sections.push(
+            main.links
+                .iter()
+                .map(|link| {
+                    format!(
+                        "target_link_libraries({} {} {})",
+                        main.cmake, scope, targets[*link].cmake
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    // Besides the project's own target, the fuzzing one is the only one built.
+    if let Some(fuzzing) = targets[1..]
+        .iter()
+        .find(|target| target.kind == TargetKind::Executable)
+    {
+        let usage = if fuzzing.links.is_empty() {
+            format!(
+                "target_include_directories({} PRIVATE ${{CMAKE_CURRENT_SOURCE_DIR}}/{})",
+                FUZZ_TARGET, INCLUDE_DIR
+            )
+        } else {
+            let links: Vec<&str> = fuzzing
+                .links
+                .iter()
+                .map(|&link| targets[link].cmake.as_str())
+                .collect();
+            format!(
+                "target_link_libraries({} PRIVATE {})",
+                FUZZ_TARGET,
+                links.join(" ")
+            )
+        };
+        sections.push(format!(
+            "option(BUILD_FUZZERS \"Build the libFuzzer targets (needs Clang)\" OFF)
+if(BUILD_FUZZERS)
+    if(NOT CMAKE_{lang}_COMPILER_ID MATCHES \"Clang\")
+        message(WARNING \"libFuzzer needs Clang, ${{CMAKE_{lang}_COMPILER_ID}} will likely fail to build the fuzzing target\")
+    endif()
+    add_executable(fuzzing {})
+    {}
+    target_compile_options(fuzzing PRIVATE -fsanitize=fuzzer,address)
+    target_link_options(fuzzing PRIVATE -fsanitize=fuzzer,address)
+endif()",
+            fuzz_harness_path(args),
+            usage,
+            lang = args.language.cmake
+        ));
+    }
+
+    sections.join("\n\n") + "\n"
+}
+
+#[derive(PartialEq)]
+enum TargetKind {
+    Executable,
+    InterfaceLibrary,
+    /// Found on the system rather than built by the project.
+    External,
+}
+
+/// A target of the generated CMakeLists.txt and the targets it links.
+struct Target {
+    name: String,
+    /// How CMakeLists.txt refers to it.
+    cmake: String,
+    kind: TargetKind,
+    /// Indices of the linked targets, as names can repeat (`-n m --link m`).
+    links: Vec<usize>,
+}
+
+/// Name of the libFuzzer target added by `--fuzz`.
+const FUZZ_TARGET: &str = "fuzzing";
+
+/// Targets `cmake_lists` defines, including the external ones they link.
+/// The project's own target comes first.
+fn targets(args: &ValidArgs) -> Vec<Target> {
+    let main = Target {
+        name: args.target.clone(),
+        cmake: "${PROJECT_NAME}".into(),
+        kind: match args.project_type {
+            ProjectType::Executable => TargetKind::Executable,
+            ProjectType::HeaderOnly => TargetKind::InterfaceLibrary,
+        },
+        links: (1..=args.links.len()).collect(),
+    };
+    let mut targets: Vec<Target> = args
+        .links
+        .iter()
+        .map(|link| Target {
+            name: link.name.clone(),
+            cmake: link.cmake.clone(),
+            kind: TargetKind::External,
+            links: Vec::new(),
+        })
+        .collect();
+    if args.fuzz {
+        targets.push(Target {
+            name: FUZZ_TARGET.into(),
+            cmake: FUZZ_TARGET.into(),
+            kind: TargetKind::Executable,
+            // A header-only library can be linked, an executable only shares
+            // its headers.
+            links: match main.kind {
+                TargetKind::InterfaceLibrary => vec![0],
+                _ => Vec::new(),
+            },
+        });
+    }
+    targets.insert(0, main);
+    targets
+}
+
+/// Graphviz description of `targets`, render it with `dot -Tsvg targets.dot`.
+fn dependency_graph(targets: &[Target]) -> String {
+    let mut graph = String::from("digraph targets {\n    rankdir=LR;\n");
+    // Nodes are numbered, a library can share the project's name.
+    for (index, target) in targets.iter().enumerate() {
+        let (kind, attributes) = match target.kind {
+            TargetKind::Executable => ("executable", "shape=box"),
+            TargetKind::InterfaceLibrary => ("interface library", "shape=box, style=rounded"),
+            TargetKind::External => ("external", "shape=ellipse, style=dashed"),
+        };
+        graph.push_str(&format!(
+            "    t{} [label=\"{}\\n{}\", {}];\n",
+            index, target.name, kind, attributes
+        ));
+    }
+    for (index, target) in targets.iter().enumerate() {
+        for link in &target.links {
+            graph.push_str(&format!("    t{} -> t{};\n", index, link));
+        }
+    }
+    graph.push_str("}\n");
+    graph
+}
+
+/// Whether `program` can be found in one of the `PATH` directories.
+fn find_program(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(program);
+        candidate.is_file() || candidate.with_extension("exe").is_file()
+    })
+}
+
+/// Oldest Ninja recommended for the generated build (multi-config support
+/// and a working jobserver came in 1.10).
+const MIN_NINJA_VERSION: (u32, u32) = (1, 10);
+
+/// Reads `major.minor` from `ninja --version` output such as `1.11.1` or
+/// `1.10.2.git.kitware.jobserver-1`.
+fn parse_ninja_version(output: &str) -> Option<(u32, u32)> {
+    let mut parts = output.lines().next()?.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+fn warn_old_ninja() {
+    let Ok(output) = std::process::Command::new("ninja")
+        .arg("--version")
+        .output()
+    else {
+        return;
+    };
+    let output = String::from_utf8_lossy(&output.stdout);
+    match parse_ninja_version(&output) {
+        Some(version) if version < MIN_NINJA_VERSION => eprintln!(
+            "Warning: ninja {}.{} is older than the recommended {}.{}, the build may fail in confusing ways.",
+            version.0, version.1, MIN_NINJA_VERSION.0, MIN_NINJA_VERSION.1
+        ),
+        Some(_) => {}
+        None => eprintln!(
+            "Warning: could not read the ninja version from \"{}\".",
+            output.trim()
+        ),
+    }
+}
+
+/// Configures and builds the freshly written project the same way the build
+/// script does.
+fn verify_project(args: &ValidArgs) -> Result<(), String> {
+    let mut configure: Vec<String> = ["-S", ".", "-B", "build", "-G"].map(String::from).to_vec();
+    configure.push(args.generator.cmake_name().into());
+    if let Some(platform) = args.platform {
+        configure.push("-A".into());
+        configure.push(platform.cmake_name().into());
+    }
+    if let Some(toolset) = &args.toolset {
+        configure.push("-T".into());
+        configure.push(toolset.clone());
+    }
+    if let Some(toolchain_file) = &args.toolchain_file {
+        configure.push(format!("-DCMAKE_TOOLCHAIN_FILE={}", toolchain_file));
+    }
+    if args.quiet_cmake {
+        configure.push("-Wno-dev".into());
+    }
+    let build = ["--build", "build"].map(String::from).to_vec();
+
+    println!("Verifying that \"{}\" builds...", args.name);
+    if args.generator == Generator::Ninja {
+        warn_old_ninja();
+    }
+    for step in [configure, build] {
+        let output = std::process::Command::new("cmake")
+            .args(&step)
+            .current_dir(&args.name)
+            .output()
+            .map_err(|err| format!("could not run cmake: {}", err))?;
+        if !output.status.success() {
+            return Err(format!(
+                "`cmake {}` failed:\n{}{}",
+                step.join(" "),
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn init_repository(args: &ValidArgs, root: &std::path::Path) {
+    let git = |git_args: &[&str]| {
+        std::process::Command::new("git")
+            .args(git_args)
+            .current_dir(root)
+            .output()
+    };
+
+    let mut commit_args: Vec<String> = Vec::new();
+    if let Some((name, email)) = &args.commit_author {
+        commit_args.push("-c".into());
+        commit_args.push(format!("user.name={}", name));
+        commit_args.push("-c".into());
+        commit_args.push(format!("user.email={}", email));
+    }
+    commit_args.extend(["commit", "-q", "-m", "Initial commit"].map(String::from));
+    let commit_args: Vec<&str> = commit_args.iter().map(String::as_str).collect();
+
+    let mut steps: Vec<&[&str]> = vec![&["init", "-q"]];
+    if args.lfs {
+        if find_program("git-lfs") {
+            steps.push(&["lfs", "install", "--local"]);
+        } else {
+            eprintln!("Warning: git-lfs was not found in PATH, assets will be committed as regular files until it is installed.");
+        }
+    }
+    steps.push(&["add", "-A"]);
+    steps.push(&commit_args);
+
+    for step in steps {
+        match git(step) {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => {
+                eprintln!(
+                    "Could not initialize git repository {}: {}",
+                    face::sad(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                return;
+            }
+            Err(err) => {
+                eprintln!("Could not run git {}: {}", face::sad(), err);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use project::scratch;
+
+    fn render_cmake(args: &ValidArgs) -> String {
+        cmake_lists(args, &targets(args))
+    }
+
+    fn args(name: &str, language: &str) -> Args {
+        Args {
+            name: Some(name.into()),
+            language: Some(language.into()),
+            ..Args::default()
+        }
+    }
+
+    #[test]
+    fn initial_commit_is_authored_by_commit_author() {
+        let dir = scratch("commit-author");
+        let args = Args {
+            git: true,
+            commit_author: Some("Bot Person <bot@x.io>".into()),
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let mut project = generate_project(&args);
+        project.root = dir.join("demo");
+        project.write().unwrap();
+
+        init_repository(&args, &project.root);
+
+        let log = std::process::Command::new("git")
+            .args(["log", "--format=%an <%ae>"])
+            .current_dir(&project.root)
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&log.stdout).trim(),
+            "Bot Person <bot@x.io>"
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn universal_sets_osx_architectures() {
+        let mut args = args("demo", "cpp").into_valid();
+        assert!(!render_cmake(&args).contains("CMAKE_OSX_ARCHITECTURES"));
+
+        // Only honoured on macOS, so set past validation.
+        args.universal = true;
+        assert!(render_cmake(&args)
+            .contains("set(CMAKE_OSX_ARCHITECTURES \"arm64;x86_64\" CACHE STRING \"\")"));
+    }
+
+    #[test]
+    fn keep_build_dir_commits_its_gitkeep() {
+        let args = Args {
+            keep_build_dir: true,
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let project = generate_project(&args);
+
+        assert!(project.contents("build/.gitkeep").is_some());
+        let gitignore = gitignore(&args);
+        assert!(gitignore.contains("build/*\n!build/.gitkeep"));
+        assert!(!gitignore.lines().any(|line| line == "build/"));
+    }
+
+    #[test]
+    fn warnings_file_flags_reach_the_cmake() {
+        let dir = scratch("warnings-file");
+        let path = dir.join("warnings.txt");
+        std::fs::write(
+            &path,
+            "# team policy\n-Wall -Wextra\ngcc: -Wlogical-op\nmsvc: /W4\n",
+        )
+        .unwrap();
+        let args = Args {
+            warnings_file: Some(path.display().to_string()),
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+
+        let cmake = render_cmake(&args);
+        assert!(cmake
+            .contains("target_compile_options(${PROJECT_NAME} PRIVATE\n    -Wall\n    -Wextra\n"));
+        assert!(cmake.contains("$<$<CXX_COMPILER_ID:GNU>:-Wlogical-op>"));
+        assert!(cmake.contains("$<$<CXX_COMPILER_ID:MSVC>:/W4>"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn rename_on_conflict_picks_the_next_free_name() {
+        let dir = scratch("rename-on-conflict");
+        let name = dir.join("foo").display().to_string();
+        let scaffold = || {
+            let args = Args {
+                rename_on_conflict: true,
+                ..args(&name, "c")
+            }
+            .into_valid();
+            generate_project(&args).write().unwrap();
+            args.name
+        };
+
+        assert_eq!(scaffold(), name);
+        assert_eq!(scaffold(), format!("{}-1", name));
+        assert!(dir.join("foo/CMakeLists.txt").is_file());
+        assert!(dir.join("foo-1/CMakeLists.txt").is_file());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn iwyu_sets_the_include_what_you_use_property() {
+        let mut args = args("demo", "cpp").into_valid();
+        assert!(!render_cmake(&args).contains("INCLUDE_WHAT_YOU_USE"));
+
+        args.iwyu = true;
+        let cmake = render_cmake(&args);
+        assert!(cmake.contains("set(CMAKE_CXX_INCLUDE_WHAT_YOU_USE"));
+        assert!(cmake.contains("--mapping_file=${CMAKE_SOURCE_DIR}/.iwyu.imp"));
+        assert!(generate_project(&args).contents(".iwyu.imp").is_some());
+    }
+
+    #[test]
+    fn listing_delimits_every_file() {
+        let listing = generate_project(&args("demo", "cpp").into_valid()).listing();
+        assert!(listing.contains("===== CMakeLists.txt =====\n"));
+        assert!(listing.contains("===== src/main.cpp =====\n#include <iostream>"));
+        assert!(listing.contains("===== build/ =====\n"));
+    }
+
+    #[test]
+    fn components_get_globbed_source_and_header_pairs() {
+        for (language, source, header) in [("cpp", ".cpp", ".hpp"), ("c", ".c", ".h")] {
+            let args = Args {
+                components: vec!["net".into(), "audio".into()],
+                ..args("demo", language)
+            }
+            .into_valid();
+            let project = generate_project(&args);
+            for component in ["net", "audio"] {
+                let source_path = format!("src/{0}/{0}{1}", component, source);
+                let header_path = format!("include/{0}/{0}{1}", component, header);
+                assert!(project.contents(source_path).is_some());
+                assert!(project.contents(header_path).is_some());
+            }
+            // GLOB_RECURSE reaches into the component folders.
+            assert!(render_cmake(&args)
+                .contains(&format!("file(GLOB_RECURSE SOURCES \"src/*{}\")", source)));
+        }
+    }
+
+    #[test]
+    fn build_script_passes_the_toolchain_file() {
+        let default = build_script(&args("demo", "cpp").into_valid());
+        assert!(!default.contains("CMAKE_TOOLCHAIN_FILE"));
+
+        let args = Args {
+            toolchain_file: Some("vcpkg/scripts/buildsystems/vcpkg.cmake".into()),
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let script = build_script(&args);
+        assert!(script
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with(" -DCMAKE_TOOLCHAIN_FILE=\"vcpkg/scripts/buildsystems/vcpkg.cmake\""));
+    }
+
+    #[test]
+    fn default_cpp_cmake_matches_the_golden_file() {
+        let cmake = render_cmake(&args("demo", "cpp").into_valid());
+        assert_eq!(cmake, include_str!("../tests/golden/CMakeLists.cpp.txt"));
+    }
+
+    #[test]
+    fn compile_features_reach_target_compile_features() {
+        let args = Args {
+            compile_features: vec!["cxx_std_20".into(), "cxx_constexpr".into()],
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let cmake = render_cmake(&args);
+        assert!(cmake
+            .contains("target_compile_features(${PROJECT_NAME} PRIVATE cxx_std_20 cxx_constexpr)"));
+        // cxx_std_20 needs a CMake that knows about C++20.
+        assert!(cmake.starts_with("cmake_minimum_required(VERSION 3.12)"));
+    }
+
+    #[test]
+    fn failed_verification_removes_the_project_only_on_request() {
+        let dir = scratch("rollback-on-verify-failure");
+        for rollback in [false, true] {
+            let args = Args {
+                verify: true,
+                rollback_on_verify_failure: rollback,
+                ..args("demo", "c")
+            }
+            .into_valid();
+            let mut project = generate_project(&args);
+            project.root = dir.join(format!("demo-{}", rollback));
+            project.write().unwrap();
+
+            verification_failed(&args, &project, "`cmake --build build` failed");
+
+            assert_eq!(project.root.exists(), !rollback);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn exit_code_main_uses_exit_success() {
+        for (language, path, header) in [
+            ("c", "src/main.c", "#include <stdlib.h>"),
+            ("cpp", "src/main.cpp", "#include <cstdlib>"),
+        ] {
+            let args = Args {
+                main_style: MainStyle::ExitCode,
+                ..args("demo", language)
+            }
+            .into_valid();
+            let project = generate_project(&args);
+            let main = project.contents(path).unwrap();
+            assert!(main.contains(header));
+            assert!(main.contains("EXIT_SUCCESS"));
+            assert!(main.contains("EXIT_FAILURE"));
+        }
+    }
+
+    #[test]
+    fn requested_standard_gets_a_compile_feature_probe() {
+        assert!(!render_cmake(&args("demo", "cpp").into_valid()).contains("FATAL_ERROR \"${"));
+
+        let args = Args {
+            standard: Some("c++20".into()),
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let cmake = render_cmake(&args);
+        assert!(cmake.contains("set(CMAKE_CXX_STANDARD 20)\nset(CMAKE_CXX_STANDARD_REQUIRED ON)"));
+        assert!(cmake.contains("if(NOT \"cxx_std_20\" IN_LIST CMAKE_CXX_COMPILE_FEATURES)"));
+        assert!(cmake.contains(
+            "message(FATAL_ERROR \"${CMAKE_CXX_COMPILER_ID} ${CMAKE_CXX_COMPILER_VERSION} does not support c++20\")"
+        ));
+    }
+
+    #[test]
+    fn default_name_is_the_current_folder() {
+        assert_eq!(
+            default_project_name(std::path::Path::new("/home/u/my-proj")),
+            Some("my-proj".to_string())
+        );
+        assert_eq!(default_project_name(std::path::Path::new("/")), None);
+    }
+
+    #[test]
+    fn single_header_generates_one_interface_header() {
+        let args = Args {
+            project_type: ProjectType::HeaderOnly,
+            single_header: true,
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let project = generate_project(&args);
+        let headers: Vec<_> = project
+            .files
+            .iter()
+            .filter(|(path, _)| path.extension().is_some_and(|ext| ext == "hpp"))
+            .collect();
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].0, std::path::Path::new("include/demo.hpp"));
+        assert!(headers[0].1.contains("#define DEMO_VERSION_MAJOR 0"));
+        let cmake = render_cmake(&args);
+        assert!(cmake.contains("add_library(${PROJECT_NAME} INTERFACE)"));
+        assert!(!cmake.contains("add_executable"));
+    }
+
+    #[test]
+    fn cmake_policy_max_adds_the_upper_endpoint() {
+        let args = Args {
+            standard: Some("c++20".into()),
+            cmake_policy_max: Some("3.28".into()),
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        assert!(render_cmake(&args).starts_with("cmake_minimum_required(VERSION 3.12...3.28)\n"));
+        assert_eq!(parse_cmake_version("3.28.1"), Some((3, 28)));
+        assert_eq!(parse_cmake_version("3"), None);
+        assert_eq!(parse_cmake_version("3.28.x"), None);
+    }
+
+    #[test]
+    fn no_glob_lists_every_source() {
+        let args = Args {
+            no_glob: true,
+            components: vec!["net".into()],
+            ..args("demo", "c")
+        }
+        .into_valid();
+        let cmake = render_cmake(&args);
+        assert!(
+            cmake.contains("add_executable(${PROJECT_NAME}\n    src/main.c\n    src/net/net.c\n)")
+        );
+        assert!(!cmake.contains("file(GLOB"));
+    }
+
+    #[test]
+    fn only_outdated_standards_get_an_advisory() {
+        let cpp = language::find("cpp").unwrap();
+        let advisory = old_standard_advisory(cpp, cpp.standard("c++98").unwrap()).unwrap();
+        assert!(advisory.contains("c++98 is outdated"));
+        assert!(advisory.contains(cpp.recommended_standard));
+        assert_eq!(
+            old_standard_advisory(cpp, cpp.standard("c++20").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn component_sources_include_their_header_relative_to_include() {
+        let args = Args {
+            components: vec!["net".into()],
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let project = generate_project(&args);
+        assert!(project
+            .contents("src/net/net.cpp")
+            .unwrap()
+            .contains("#include \"net/net.hpp\""));
+        assert!(project.contents("include/net/net.hpp").is_some());
+
+        let cmake = render_cmake(&args);
+        let include_dirs: Vec<&str> = cmake
+            .lines()
+            .filter(|line| line.starts_with("target_include_directories"))
+            .collect();
+        assert_eq!(
+            include_dirs,
+            ["target_include_directories(${PROJECT_NAME} PRIVATE ${CMAKE_CURRENT_SOURCE_DIR}/include)"]
+        );
+    }
+
+    #[test]
+    fn in_source_builds_are_refused_unless_allowed() {
+        let guard = "if(CMAKE_SOURCE_DIR STREQUAL CMAKE_BINARY_DIR)";
+        let default = render_cmake(&args("demo", "cpp").into_valid());
+        assert!(default.contains(guard));
+        assert!(default.find(guard) < default.find("project("));
+
+        let allowed = Args {
+            allow_in_source_build: true,
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        assert!(!render_cmake(&allowed).contains(guard));
+    }
+
+    #[test]
+    fn empty_generated_folders_get_a_gitkeep() {
+        let executable = generate_project(&args("demo", "cpp").into_valid());
+        assert!(executable.contents("include/.gitkeep").is_some());
+        assert!(executable.contents("src/.gitkeep").is_none());
+        assert!(executable.contents("build/.gitkeep").is_none());
+
+        // Folders that get real files are left alone.
+        let header_only = generate_project(
+            &Args {
+                project_type: ProjectType::HeaderOnly,
+                ..args("demo", "cpp")
+            }
+            .into_valid(),
+        );
+        assert!(header_only.contents("include/.gitkeep").is_none());
+    }
+
+    #[test]
+    fn post_message_fills_its_placeholders() {
+        let message = post_message(
+            "cd {path} && ./build.sh # {project}, {project}",
+            "demo",
+            std::path::Path::new("/work/demo"),
+        );
+        assert_eq!(message, "cd /work/demo && ./build.sh # demo, demo");
+        assert_eq!(
+            post_message("no placeholders", "demo", std::path::Path::new("demo")),
+            "no placeholders"
+        );
+    }
+
+    #[test]
+    fn quiet_cmake_hides_developer_warnings() {
+        let default = build_script(&args("demo", "cpp").into_valid());
+        assert!(!default.contains("-Wno-dev"));
+
+        let quiet = build_script(
+            &Args {
+                quiet_cmake: true,
+                ..args("demo", "cpp")
+            }
+            .into_valid(),
+        );
+        assert!(quiet.starts_with("cmake -S . -B build -G \"Ninja\" -Wno-dev\n"));
+    }
+
+    #[test]
+    fn lfs_tracks_every_binary_extension() {
+        let attributes = lfs_attributes();
+        assert_eq!(attributes.lines().count(), LFS_EXTENSIONS.len());
+        for extension in ["png", "wav", "fbx", "ttf"] {
+            assert!(
+                attributes.contains(&format!(
+                    "*.{} filter=lfs diff=lfs merge=lfs -text\n",
+                    extension
+                )),
+                "{} is not tracked",
+                extension
+            );
+        }
+
+        let project = generate_project(
+            &Args {
+                lfs: true,
+                ..args("demo", "cpp")
+            }
+            .into_valid(),
+        );
+        assert_eq!(
+            project.contents(".gitattributes"),
+            Some(attributes.as_str())
+        );
+    }
+
+    #[test]
+    fn vscode_recommends_the_extensions_features_rely_on() {
+        let mut args = args("demo", "cpp").into_valid();
+        assert_eq!(vscode_recommendations(&args), VSCODE_RECOMMENDATIONS);
+
+        args.toolchain = Some(Toolchain::Zig);
+        args.iwyu = true;
+        let recommendations = vscode_recommendations(&args);
+        assert!(recommendations.contains(&"ziglang.vscode-zig"));
+        assert!(recommendations.contains(&"llvm-vs-code-extensions.vscode-clangd"));
+        assert!(vscode_extensions(&args).contains("        \"ziglang.vscode-zig\""));
+    }
+
+    #[test]
+    fn symbol_prefix_reaches_guards_and_declarations() {
+        let prefixed = Args {
+            symbol_prefix: Some("foo_".into()),
+            ..args("demo", "c")
+        }
+        .into_valid();
+        assert_eq!(include_guard(&prefixed, "net/net.h"), "FOO_NET_NET_H");
+
+        let header = component_header(&prefixed, "net", "net/net.h");
+        assert!(header.starts_with("#ifndef FOO_NET_NET_H\n#define FOO_NET_NET_H\n"));
+        assert!(header.contains("void foo_net_hello(void);"));
+
+        let unprefixed = args("demo", "c").into_valid();
+        assert_eq!(include_guard(&unprefixed, "net/net.h"), "NET_NET_H");
+
+        let library = generate_project(
+            &Args {
+                project_type: ProjectType::HeaderOnly,
+                symbol_prefix: Some("foo_".into()),
+                ..args("lib", "c")
+            }
+            .into_valid(),
+        );
+        let header = library.contents("include/lib/lib.h").unwrap();
+        assert!(header.starts_with("#ifndef FOO_LIB_LIB_H\n"));
+        assert!(header.contains("static inline int foo_add(int a, int b)"));
+        assert!(!header.contains(" lib_"));
+        let version = library.contents("include/lib/version.h").unwrap();
+        assert!(version.starts_with("#ifndef FOO_LIB_VERSION_H\n"));
+        assert!(version.contains("#define FOO_VERSION_MAJOR 0"));
+        assert!(!version.contains("LIB_VERSION_MAJOR"));
+    }
+
+    #[test]
+    fn compile_flags_follow_the_language_and_standard() {
+        let flags = compile_flags(
+            &Args {
+                standard: Some("c++20".into()),
+                ..args("demo", "cpp")
+            }
+            .into_valid(),
+        );
+        assert_eq!(flags, "-xc++\n-std=c++20\n-Iinclude\n");
+
+        let c = compile_flags(&args("demo", "c").into_valid());
+        assert_eq!(c, "-xc\n-Iinclude\n");
+    }
+
+    #[test]
+    fn links_reach_target_link_libraries() {
+        let cmake = render_cmake(
+            &Args {
+                links: vec!["m".into(), "threads".into(), "pthread".into()],
+                ..args("demo", "cpp")
+            }
+            .into_valid(),
+        );
+        assert!(cmake.contains("target_link_libraries(${PROJECT_NAME} PRIVATE m)"));
+        assert!(cmake.contains("find_package(Threads REQUIRED)"));
+        assert_eq!(cmake.matches("find_package(Threads REQUIRED)").count(), 1);
+        assert_eq!(
+            cmake
+                .matches("target_link_libraries(${PROJECT_NAME} PRIVATE Threads::Threads)")
+                .count(),
+            1
+        );
+        assert!(cmake.find("find_package(Threads") < cmake.find("Threads::Threads"));
+    }
+
+    #[test]
+    fn reproduce_round_trips_the_recorded_options() {
+        let dir = scratch("reproduce");
+        let include = dir.join("extra.cmake");
+        std::fs::write(&include, "message(STATUS \"extra\")\n").unwrap();
+        let original = Args {
+            standard: Some("c++20".into()),
+            compile_features: vec!["cxx_constexpr".into()],
+            cmake_includes: vec![include.display().to_string()],
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let mut project = generate_project(&original);
+        project.root = dir.join("demo");
+        project.write().unwrap();
+        let marker = project.root.display().to_string();
+
+        let reproduced = reproduce_args(&marker, None, Args::default(), &[]);
+        assert_eq!(reproduced.name.as_deref(), Some("demo"));
+        assert_eq!(reproduced.language.as_deref(), Some("CPP"));
+        assert_eq!(reproduced.standard.as_deref(), Some("c++20"));
+        assert_eq!(reproduced.compile_features, ["cxx_constexpr"]);
+        // Resolved to the copy inside the reproduced project.
+        assert_eq!(
+            reproduced.cmake_includes,
+            [dir.join("demo/cmake/extra.cmake").display().to_string()]
+        );
+        assert!(reproduced.clone().into_valid().cmake_includes[0]
+            .1
+            .contains("message(STATUS \"extra\")"));
+
+        let cli = Args {
+            standard: Some("c++23".into()),
+            verify: true,
+            archive: Some("demo.tar.gz".into()),
+            ..Args::default()
+        };
+        let merged = reproduce_args(&marker, Some("again".into()), cli, &["standard".into()]);
+        assert_eq!(merged.name.as_deref(), Some("again"));
+        assert_eq!(merged.standard.as_deref(), Some("c++23"));
+        assert_eq!(merged.compile_features, ["cxx_constexpr"]);
+        assert!(merged.verify);
+        assert_eq!(merged.archive.as_deref(), Some("demo.tar.gz"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn cancelled_prompts_exit_with_130() {
+        let (message, code) = prompt_failure(&InquireError::OperationCanceled);
+        assert!(message.starts_with("Cancelled"));
+        assert_eq!(code, 130);
+        assert_eq!(prompt_failure(&InquireError::OperationInterrupted).1, 130);
+
+        let (message, code) = prompt_failure(&InquireError::NotTTY);
+        assert!(message.starts_with("Could not read the answer"));
+        assert_eq!(code, 1);
+    }
+
+    #[test]
+    fn reads_ninja_version_samples() {
+        assert_eq!(parse_ninja_version("1.11.1\n"), Some((1, 11)));
+        assert_eq!(
+            parse_ninja_version("1.10.2.git.kitware.jobserver-1\n"),
+            Some((1, 10))
+        );
+        assert_eq!(parse_ninja_version("1.8\n"), Some((1, 8)));
+        assert_eq!(parse_ninja_version(""), None);
+        assert_eq!(parse_ninja_version("ninja: command not found"), None);
+    }
+
+    #[test]
+    fn target_names_are_transliterated_to_ascii() {
+        assert_eq!(target_name("café"), "cafe");
+        assert_eq!(target_name("my lib"), "my_lib");
+        assert_eq!(target_name("demo-1.0+x"), "demo-1.0+x");
+
+        let args = args("café", "cpp").into_valid();
+        assert_eq!(args.name, "café");
+        assert_eq!(args.target, "cafe");
+        assert!(render_cmake(&args).contains("set(PROJECT_NAME cafe)"));
+    }
+
+    #[test]
+    fn vs_build_script_passes_platform_and_toolset() {
+        let vs = Args {
+            generator: Generator::Vs,
+            platform: Some(Platform::X64),
+            toolset: Some("ClangCL".into()),
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        let script = build_script(&vs);
+        assert!(script.starts_with("@echo off\r\ncmake -S . -B build -G \""));
+        assert!(script.contains(" -A x64 -T ClangCL\r\n"));
+        assert!(script.contains("build\\Debug\\demo.exe\r\n"));
+
+        let project = generate_project(&vs);
+        assert_eq!(project.contents("build.bat"), Some(script.as_str()));
+    }
+
+    #[test]
+    fn fuzz_adds_a_harness_and_sanitized_target() {
+        let mut args = args("demo", "cpp").into_valid();
+        args.fuzz = true;
+        let cmake = render_cmake(&args);
+        assert!(cmake.contains("option(BUILD_FUZZERS"));
+        assert!(cmake.contains("add_executable(fuzzing fuzz/fuzz_target.cpp)"));
+        assert!(cmake.contains("target_compile_options(fuzzing PRIVATE -fsanitize=fuzzer,address)"));
+        assert!(cmake.contains("target_link_options(fuzzing PRIVATE -fsanitize=fuzzer,address)"));
+        assert!(cmake.starts_with("cmake_minimum_required(VERSION 3.13)"));
+
+        let project = generate_project(&args);
+        let harness = project.contents("fuzz/fuzz_target.cpp").unwrap();
+        assert!(harness
+            .contains("extern \"C\" int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size)"));
+        assert!(project.dirs.contains(&FUZZ_CORPUS_DIR.into()));
+    }
+
+    #[test]
+    fn plain_name_errors_are_ascii() {
+        // Plain mode is process-wide, put it back even if an assertion fails.
+        struct Restore(bool);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                face::set_plain(self.0);
+            }
+        }
+        let _restore = Restore(face::set_plain(true));
+
+        for name in ["", "   ", "a/b"] {
+            match validate_name(name) {
+                Validation::Invalid(inquire::validator::ErrorMessage::Custom(message)) => {
+                    assert!(message.is_ascii(), "{:?} is not ASCII", message)
+                }
+                _ => panic!("{:?} was accepted", name),
+            }
+        }
+        assert!(matches!(validate_name("demo"), Validation::Valid));
+    }
+
+    #[test]
+    fn zig_toolchain_sets_the_compiler_before_project() {
+        let zig = |language: &str| {
+            render_cmake(
+                &Args {
+                    toolchain: Some(Toolchain::Zig),
+                    ..args("demo", language)
+                }
+                .into_valid(),
+            )
+        };
+        let cpp = zig("cpp");
+        assert!(cpp.contains("set(CMAKE_CXX_COMPILER \"zig;c++\")"));
+        assert!(cpp.find("CMAKE_CXX_COMPILER") < cpp.find("project("));
+        assert!(zig("c").contains("set(CMAKE_C_COMPILER \"zig;cc\")"));
+
+        assert!(!render_cmake(&args("demo", "cpp").into_valid()).contains("zig"));
+    }
+
+    #[test]
+    fn excludes_are_anchored_to_the_project() {
+        assert_eq!(glob_to_regex("src/gen/**").unwrap(), "^src/gen/.*$");
+        assert_eq!(glob_to_regex("./src/*.c").unwrap(), "^src/[^/]*\\\\.c$");
+        assert_eq!(glob_to_regex("*_win.c").unwrap(), "(^|/)[^/]*_win\\\\.c$");
+        assert_eq!(glob_to_regex("../src/*.c"), None);
+        assert_eq!(glob_to_regex("/src/*.c"), None);
+
+        let cmake = render_cmake(
+            &Args {
+                excludes: vec!["src/gen/**".into()],
+                ..args("demo", "cpp")
+            }
+            .into_valid(),
+        );
+        let glob = cmake.find(
+            "file(GLOB_RECURSE SOURCES RELATIVE \"${CMAKE_CURRENT_SOURCE_DIR}\" \"src/*.cpp\")",
+        );
+        let filter = cmake.find("list(FILTER SOURCES EXCLUDE REGEX \"^src/gen/.*$\")");
+        assert!(glob.is_some() && glob < filter);
+        assert!(filter < cmake.find("add_executable(${PROJECT_NAME} ${SOURCES})"));
+    }
+
+    #[test]
+    fn cmake_includes_are_copied_and_included() {
+        let dir = scratch("cmake-include");
+        let include = dir.join("my warnings.cmake");
+        std::fs::write(&include, "add_compile_options(-Wall)\n").unwrap();
+        let args = Args {
+            cmake_includes: vec![include.display().to_string()],
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+
+        let project = generate_project(&args);
+        assert_eq!(
+            project.contents("cmake/my warnings.cmake"),
+            Some("add_compile_options(-Wall)\n")
+        );
+        let cmake = render_cmake(&args);
+        assert!(cmake.contains("include(\"${CMAKE_CURRENT_SOURCE_DIR}/cmake/my warnings.cmake\")"));
+        assert!(cmake.find("include(\"") > cmake.find("project("));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn dependency_graph_matches_the_cmake_targets() {
+        let mut header_only = Args {
+            project_type: ProjectType::HeaderOnly,
+            links: vec!["dl".into()],
+            ..args("demo", "cpp")
+        }
+        .into_valid();
+        header_only.fuzz = true;
+        let graph_targets = targets(&header_only);
+
+        let graph = dependency_graph(&graph_targets);
+        assert!(graph.contains("    t0 [label=\"demo\\ninterface library\""));
+        assert!(graph.contains("    t1 [label=\"dl\\nexternal\""));
+        assert!(graph.contains("    t2 [label=\"fuzzing\\nexecutable\""));
+        assert!(graph.contains("    t2 -> t0;\n"));
+        assert!(graph.contains("    t0 -> t1;\n"));
+
+        let linked = cmake_lists(&header_only, &graph_targets);
+        assert!(linked.contains("target_link_libraries(fuzzing PRIVATE ${PROJECT_NAME})"));
+        assert!(
+            linked.contains("target_link_libraries(${PROJECT_NAME} INTERFACE ${CMAKE_DL_LIBS})")
+        );
+
+        // An executable cannot be linked, the fuzzing target only gets its headers.
+        let mut executable = args("demo", "cpp").into_valid();
+        executable.fuzz = true;
+        assert!(!dependency_graph(&targets(&executable)).contains("t1 ->"));
+        assert!(render_cmake(&executable).contains(
+            "target_include_directories(fuzzing PRIVATE ${CMAKE_CURRENT_SOURCE_DIR}/include)"
+        ));
+    }
+
+    #[test]
+    fn reproduce_reads_the_warnings_copied_into_a_nested_project() {
+        let dir = scratch("reproduce-nested");
+        let policy = dir.join("w.txt");
+        std::fs::write(&policy, "-Wall\ngcc: -Wlogical-op\n").unwrap();
+        let original = Args {
+            warnings_file: Some(policy.display().to_string()),
+            ..args("nest/deep", "c")
+        }
+        .into_valid();
+        let mut project = generate_project(&original);
+        assert_eq!(
+            project.contents(WARNINGS_FILE),
+            Some("-Wall\ngcc: -Wlogical-op\n")
+        );
+        std::fs::create_dir(dir.join("nest")).unwrap();
+        project.root = dir.join("nest/deep");
+        project.write().unwrap();
+        // The reproduced project no longer needs the original file.
+        std::fs::remove_file(&policy).unwrap();
+
+        let marker = project.root.display().to_string();
+        let reproduced = reproduce_args(&marker, None, Args::default(), &[]);
+        assert_eq!(
+            reproduced.warnings_file,
+            Some(
+                dir.join("nest/deep")
+                    .join(WARNINGS_FILE)
+                    .display()
+                    .to_string()
+            )
+        );
+        assert_eq!(reproduced.into_valid().warnings, original.warnings);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn links_named_like_the_project_are_not_the_project() {
+        for (name, link, cmake) in [("m", "m", "m"), ("threads", "threads", "Threads::Threads")] {
+            let project = Args {
+                links: vec![link.into()],
+                dep_graph: true,
+                ..args(name, "c")
+            }
+            .into_valid();
+            let targets = targets(&project);
+            assert_eq!(targets[0].links, [1]);
+            assert!(targets[1].links.is_empty());
+
+            let cmake_lists = cmake_lists(&project, &targets);
+            assert!(cmake_lists.contains(&format!(
+                "target_link_libraries(${{PROJECT_NAME}} PRIVATE {})",
+                cmake
+            )));
+            assert!(!cmake_lists.contains("PRIVATE ${PROJECT_NAME})"));
+
+            let graph = dependency_graph(&targets);
+            assert!(graph.contains(&format!("    t0 [label=\"{}\\nexecutable\"", name)));
+            assert!(graph.contains(&format!("    t1 [label=\"{}\\nexternal\"", link)));
+            assert!(graph.contains("    t0 -> t1;\n"));
+            assert!(!graph.contains("t0 -> t0") && !graph.contains("t1 -> "));
         }
     }
 }
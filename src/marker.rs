@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Args;
+
+/// Name of the file recording how a project was generated.
+pub const FILE_NAME: &str = ".cppgen.toml";
+
+/// Template sets bundled with this build, oldest first. Bump (and keep the
+/// previous one selectable) whenever the generated output changes shape.
+pub const TEMPLATE_SETS: &[u32] = &[1];
+
+/// Contents of `.cppgen.toml`.
+#[derive(Serialize, Deserialize)]
+pub struct Marker {
+    pub cppgen_version: String,
+    pub template_set: u32,
+    /// Options the project was generated with.
+    pub options: Args,
+}
+
+impl Marker {
+    pub fn new(template_set: u32, options: Args) -> Self {
+        Marker {
+            cppgen_version: env!("CARGO_PKG_VERSION").to_string(),
+            template_set,
+            options,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let toml = toml::to_string(self).expect("marker options are always serializable");
+        format!(
+            "# Generated by cppgen, records how this project was scaffolded.\n{}",
+            toml
+        )
+    }
+
+    /// Reads the marker at `path`, or the one inside `path` when it is a
+    /// project folder.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let path = if path.is_dir() {
+            path.join(FILE_NAME)
+        } else {
+            path.to_path_buf()
+        };
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Could not read \"{}\": {}", path.display(), err))?;
+        toml::from_str(&contents)
+            .map_err(|err| format!("\"{}\" is not a valid marker: {}", path.display(), err))
+    }
+}
+
+/// The newest bundled template set.
+pub fn latest_template_set() -> u32 {
+    TEMPLATE_SETS[TEMPLATE_SETS.len() - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_the_template_set() {
+        let rendered = Marker::new(latest_template_set(), Args::default()).render();
+        assert!(rendered.contains(&format!("template_set = {}", latest_template_set())));
+        assert!(rendered.contains(&format!(
+            "cppgen_version = \"{}\"",
+            env!("CARGO_PKG_VERSION")
+        )));
+
+        let read: Marker = toml::from_str(&rendered).unwrap();
+        assert_eq!(read.template_set, latest_template_set());
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use sha2::{Digest, Sha256};
+
+use crate::face;
+
+/// Every directory and file of a project, computed in memory before
+/// anything is written to disk.
+pub struct GeneratedProject {
+    pub root: PathBuf,
+    /// Directories (relative to `root`) that must exist even without files.
+    pub dirs: Vec<PathBuf>,
+    /// Files (relative to `root`) and their contents.
+    pub files: Vec<(PathBuf, String)>,
+}
+
+impl GeneratedProject {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        GeneratedProject {
+            root: root.into(),
+            dirs: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+
+    pub fn dir(&mut self, path: impl AsRef<Path>) {
+        self.dirs.push(path.as_ref().to_path_buf());
+    }
+
+    pub fn file(&mut self, path: impl AsRef<Path>, contents: impl Into<String>) {
+        self.files
+            .push((path.as_ref().to_path_buf(), contents.into()));
+    }
+
+    /// Directories that no generated file lives in.
+    pub fn empty_dirs(&self) -> impl Iterator<Item = &PathBuf> {
+        self.dirs
+            .iter()
+            .filter(|dir| !self.files.iter().any(|(path, _)| path.starts_with(dir)))
+    }
+
+    /// Lets the user change the files before they are written: copies them to
+    /// a temporary folder, waits for `editor` to exit and reads them back.
+    pub fn edit_with(&mut self, editor: &str) -> Result<(), String> {
+        let mut command = editor.split_whitespace();
+        let program = command.next().ok_or("No editor configured (set $EDITOR)")?;
+
+        let temp = std::env::temp_dir().join(format!("cppgen-edit-{}", std::process::id()));
+        let result = self.edit_in(&temp, program, command.collect());
+        let _ = std::fs::remove_dir_all(&temp);
+        result
+    }
+
+    fn edit_in(&mut self, temp: &Path, program: &str, args: Vec<&str>) -> Result<(), String> {
+        let error = |err: std::io::Error| format!("Could not prepare files for editing: {}", err);
+
+        let editable: Vec<usize> = (0..self.files.len())
+            .filter(|&index| !self.files[index].1.is_empty())
+            .collect();
+        for &index in &editable {
+            let path = temp.join(&self.files[index].0);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(error)?;
+            }
+            std::fs::write(&path, &self.files[index].1).map_err(error)?;
+        }
+
+        let status = std::process::Command::new(program)
+            .args(args)
+            .args(
+                editable
+                    .iter()
+                    .map(|&index| temp.join(&self.files[index].0)),
+            )
+            .status()
+            .map_err(|err| format!("Could not run \"{}\": {}", program, err))?;
+        if !status.success() {
+            return Err(format!("\"{}\" exited with {}", program, status));
+        }
+
+        for &index in &editable {
+            self.files[index].1 =
+                std::fs::read_to_string(temp.join(&self.files[index].0)).map_err(error)?;
+        }
+        Ok(())
+    }
+
+    /// Renders every file as a single pasteable blob, each one introduced by
+    /// a `===== path =====` line. Empty folders are listed with a trailing `/`.
+    pub fn listing(&self) -> String {
+        let mut listing = String::new();
+        for (path, contents) in &self.files {
+            listing.push_str(&format!("===== {} =====\n{}", path.display(), contents));
+            if !contents.ends_with('\n') {
+                listing.push('\n');
+            }
+        }
+        for dir in self.empty_dirs() {
+            listing.push_str(&format!("===== {}/ =====\n", dir.display()));
+        }
+        listing
+    }
+
+    /// JSON list of every generated file (relative to the project folder) with
+    /// its size in bytes and SHA-256, for tools that track generated output.
+    pub fn manifest(&self) -> String {
+        #[derive(serde::Serialize)]
+        struct Entry {
+            path: String,
+            size: usize,
+            sha256: String,
+        }
+
+        let entries: Vec<Entry> = self
+            .files
+            .iter()
+            .map(|(path, contents)| {
+                let sha256: String = Sha256::digest(contents.as_bytes())
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect();
+                Entry {
+                    path: path.to_string_lossy().replace('\\', "/"),
+                    size: contents.len(),
+                    sha256,
+                }
+            })
+            .collect();
+        serde_json::to_string_pretty(&entries).expect("manifest entries are plain JSON") + "\n"
+    }
+
+    /// Writes the project as a gzip tarball at `path`, with every entry under a
+    /// top-level folder named after the project.
+    pub fn write_archive(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let error = |err: std::io::Error| {
+            format!(
+                "Could not write \"{}\" {}: {}",
+                path.display(),
+                face::sad(),
+                err
+            )
+        };
+
+        let file = std::fs::File::create(path).map_err(error)?;
+        let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        for dir in self.empty_dirs() {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_mtime(mtime);
+            header.set_size(0);
+            archive
+                .append_data(&mut header, self.root.join(dir), std::io::empty())
+                .map_err(error)?;
+        }
+
+        for (file_path, contents) in &self.files {
+            let mut header = tar::Header::new_gnu();
+            let executable = file_path.extension().is_some_and(|ext| ext == "sh");
+            header.set_mode(if executable { 0o755 } else { 0o644 });
+            header.set_mtime(mtime);
+            header.set_size(contents.len() as u64);
+            archive
+                .append_data(&mut header, self.root.join(file_path), contents.as_bytes())
+                .map_err(error)?;
+        }
+
+        archive
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(error)?;
+        Ok(())
+    }
+
+    /// Writes the project to disk. Directories are only created alongside the
+    /// files that live in them (or at the very end for empty ones), and the
+    /// whole project folder is removed if anything fails, so a failed run
+    /// never leaves partial state behind.
+    pub fn write(&self) -> Result<(), String> {
+        if let Err(err) = std::fs::create_dir(&self.root) {
+            return Err(if err.kind() == ErrorKind::AlreadyExists {
+                format!(
+                    "\"{}\" folder already exists {}",
+                    self.root.display(),
+                    face::sad()
+                )
+            } else {
+                format!("Could not create project folder {}: {}", face::sad(), err)
+            });
+        }
+
+        self.write_contents().inspect_err(|_| self.remove())
+    }
+
+    /// Deletes the project folder and everything in it.
+    pub fn remove(&self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+
+    fn write_contents(&self) -> Result<(), String> {
+        for (path, contents) in &self.files {
+            let full_path = self.root.join(path);
+            if let Some(parent) = full_path.parent() {
+                std::fs::create_dir_all(parent).map_err(|err| {
+                    format!(
+                        "Could not create \"{}\" folder {}: {}",
+                        path.parent().unwrap_or(path).display(),
+                        face::sad(),
+                        err
+                    )
+                })?;
+            }
+            std::fs::write(&full_path, contents).map_err(|err| {
+                format!(
+                    "Could not create \"{}\" {}: {}",
+                    path.display(),
+                    face::sad(),
+                    err
+                )
+            })?;
+        }
+
+        for dir in &self.dirs {
+            std::fs::create_dir_all(self.root.join(dir)).map_err(|err| {
+                format!(
+                    "Could not create \"{}\" folder {}: {}",
+                    dir.display(),
+                    face::sad(),
+                    err
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl GeneratedProject {
+    /// Contents of the generated file at `path`.
+    pub fn contents(&self, path: impl AsRef<Path>) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|(file, _)| file == path.as_ref())
+            .map(|(_, contents)| contents.as_str())
+    }
+}
+
+/// Fresh empty folder under the system temp dir for `test`.
+#[cfg(test)]
+pub fn scratch(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cppgen-test-{}-{}", std::process::id(), test));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_write_leaves_nothing_behind() {
+        let dir = scratch("failed-write");
+        let mut project = GeneratedProject::new(dir.join("demo"));
+        project.dir("include");
+        project.file("src", "a file where a folder is needed");
+        project.file("src/main.c", "int main(void) { return 0; }\n");
+
+        assert!(project.write().is_err());
+        assert!(!project.root.exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn archive_extracts_to_the_project() {
+        let dir = scratch("archive");
+        let mut project = GeneratedProject::new("demo");
+        project.dir("include");
+        project.file("CMakeLists.txt", "project(demo)\n");
+        project.file("build.sh", "cmake --build build\n");
+        let archive = dir.join("demo.tar.gz");
+        project.write_archive(&archive).unwrap();
+
+        let file = std::fs::File::open(&archive).unwrap();
+        tar::Archive::new(flate2::read::GzDecoder::new(file))
+            .unpack(&dir)
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dir.join("demo/CMakeLists.txt")).unwrap(),
+            "project(demo)\n"
+        );
+        assert!(dir.join("demo/include").is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.join("demo/build.sh"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn edited_files_are_read_back() {
+        let dir = scratch("edit");
+        let mut project = GeneratedProject::new("demo");
+        project.file("CMakeLists.txt", "project(demo)\n");
+        project.file("src/main.c", "int main(void) { return 0; }\n");
+        project.file("include/.gitkeep", "");
+
+        // Stands in for $EDITOR: renames the project in every file it is given.
+        let script = "for file in \"$@\"; do sed -i 's/demo/edited/' \"$file\"; done";
+        project
+            .edit_in(&dir, "sh", vec!["-c", script, "editor"])
+            .unwrap();
+        assert_eq!(project.files[0].1, "project(edited)\n");
+        assert_eq!(project.files[1].1, "int main(void) { return 0; }\n");
+        assert_eq!(project.files[2].1, "");
+
+        let err = project.edit_in(&dir, "false", Vec::new()).unwrap_err();
+        assert!(err.contains("\"false\" exited with"));
+        assert_eq!(project.files[0].1, "project(edited)\n");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn manifest_lists_sizes_and_hashes() {
+        let mut project = GeneratedProject::new("demo");
+        project.dir("include");
+        project.file("CMakeLists.txt", "project(demo)\n");
+        project.file("src/main.c", "");
+
+        let manifest: serde_json::Value = serde_json::from_str(&project.manifest()).unwrap();
+        let entries = manifest.as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["path"], "CMakeLists.txt");
+        assert_eq!(entries[0]["size"], 14);
+        assert_eq!(
+            entries[1],
+            serde_json::json!({
+                "path": "src/main.c",
+                "size": 0,
+                "sha256": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            })
+        );
+        assert_eq!(entries[0]["sha256"].as_str().unwrap().len(), 64);
+    }
+}
@@ -0,0 +1,236 @@
+//! Hidden `--self-check`: renders every language, standard, project type and
+//! generator, each with every way of listing sources, main style, header
+//! layout, toolchain and gitignore kind and all other features enabled, in
+//! memory, and reports any file that came out empty or with a leftover format
+//! placeholder. Catches templates that were not bundled or not filled in
+//! without touching the disk.
+
+use crate::{gitignore, language, marker, Args, Generator, MainStyle, ProjectType, Toolchain};
+
+/// Runs the check and returns the exit code.
+pub fn run() -> i32 {
+    let mut problems = Vec::new();
+    let mut checked = 0;
+
+    for combination in combinations() {
+        let label = label(&combination);
+        let toolchain = combination.toolchain;
+        let mut valid = Args {
+            toolchain: None,
+            ..combination
+        }
+        .into_valid();
+        // Turned on past validation so the missing-tool warnings stay quiet.
+        valid.iwyu = true;
+        valid.fuzz = true;
+        valid.toolchain = toolchain;
+        let project = crate::generate_project(&valid);
+        checked += 1;
+
+        for (path, contents) in &project.files {
+            let path = path.display().to_string();
+            if contents.is_empty() && !path.ends_with(".gitkeep") {
+                problems.push(format!("{}: {} is empty", label, path));
+            }
+            if let Some(placeholder) = ["{}", "{{", "}}"].iter().find(|p| contents.contains(*p)) {
+                problems.push(format!(
+                    "{}: {} has an unresolved \"{}\"",
+                    label, path, placeholder
+                ));
+            }
+            if path == marker::FILE_NAME && toml::from_str::<marker::Marker>(contents).is_err() {
+                problems.push(format!("{}: {} does not read back", label, path));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        println!("Self-check passed: {} combinations rendered.", checked);
+        0
+    } else {
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        eprintln!(
+            "Self-check failed: {} problems in {} combinations.",
+            problems.len(),
+            checked
+        );
+        1
+    }
+}
+
+/// Names the language, standard, project type, generator and any variation
+/// of `args`.
+fn label(args: &Args) -> String {
+    let mut label = vec![
+        args.language.clone().unwrap_or_default(),
+        args.standard.clone().unwrap_or("(no standard)".into()),
+        match args.project_type {
+            ProjectType::Executable => "executable".into(),
+            ProjectType::HeaderOnly => "header-only".into(),
+        },
+        match args.generator {
+            Generator::Ninja => "ninja".into(),
+            Generator::Vs => "vs".into(),
+        },
+    ];
+    let variations = [
+        (args.no_glob, "no-glob"),
+        (!args.excludes.is_empty(), "excludes"),
+        (args.single_header, "single-header"),
+        (args.main_style == MainStyle::ExitCode, "exit-code"),
+        (args.toolchain.is_some(), "zig"),
+        (args.gitignore.is_empty(), "language-gitignore"),
+    ];
+    for (varied, name) in variations {
+        if varied {
+            label.push(name.into());
+        }
+    }
+    label.join(" ")
+}
+
+fn combinations() -> Vec<Args> {
+    let mut combinations = Vec::new();
+    for language in language::LANGUAGES {
+        let standards = std::iter::once(None).chain(language.standards.iter().map(Some));
+        for standard in standards {
+            for project_type in [ProjectType::Executable, ProjectType::HeaderOnly] {
+                for generator in [Generator::Ninja, Generator::Vs] {
+                    let args = everything(language, standard, project_type, generator);
+                    combinations.extend(variations(args));
+                }
+            }
+        }
+    }
+    combinations
+}
+
+/// Options turning on every feature that applies to the combination.
+fn everything(
+    language: &'static language::Language,
+    standard: Option<&'static language::Standard>,
+    project_type: ProjectType,
+    generator: Generator,
+) -> Args {
+    let executable = project_type == ProjectType::Executable;
+    Args {
+        name: Some("self_check".into()),
+        language: Some(language.name.into()),
+        standard: standard.map(|standard| standard.name.into()),
+        project_type,
+        generator,
+        allow_old_standard: true,
+        keep_build_dir: true,
+        components: if executable {
+            vec!["core".into()]
+        } else {
+            Vec::new()
+        },
+        compile_features: vec![language.compile_features[0].into()],
+        cmake_policy_max: Some("3.28".into()),
+        gitignore: gitignore::FRAGMENTS
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        links: vec!["threads".into(), "m".into()],
+        symbol_prefix: (!language.namespaces).then(|| "sc_".into()),
+        lfs: true,
+        vscode: true,
+        compile_flags_txt: true,
+        dep_graph: true,
+        ..Args::default()
+    }
+}
+
+/// `args` once for every combination of the feature axes that apply to it.
+fn variations(args: Args) -> Vec<Args> {
+    let executable = args.project_type == ProjectType::Executable;
+    let mut variations = vec![args];
+    let mut split = |vary: &dyn Fn(&Args) -> Vec<Args>| {
+        variations = variations.iter().flat_map(vary).collect();
+    };
+
+    if executable {
+        split(&|args| {
+            vec![
+                args.clone(),
+                Args {
+                    no_glob: true,
+                    ..args.clone()
+                },
+                Args {
+                    excludes: vec!["src/gen/**".into(), "*_win.c".into()],
+                    ..args.clone()
+                },
+            ]
+        });
+        split(&|args| {
+            [MainStyle::Classic, MainStyle::ExitCode]
+                .map(|main_style| Args {
+                    main_style,
+                    ..args.clone()
+                })
+                .to_vec()
+        });
+    } else {
+        split(&|args| {
+            vec![
+                args.clone(),
+                Args {
+                    single_header: true,
+                    ..args.clone()
+                },
+            ]
+        });
+    }
+    // The Visual Studio generator picks its own compiler.
+    split(&|args| match args.generator {
+        Generator::Ninja => vec![
+            args.clone(),
+            Args {
+                toolchain: Some(Toolchain::Zig),
+                ..args.clone()
+            },
+        ],
+        Generator::Vs => vec![args.clone()],
+    });
+    split(&|args| {
+        vec![
+            args.clone(),
+            Args {
+                gitignore: Vec::new(),
+                ..args.clone()
+            },
+        ]
+    });
+    variations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_combination_renders() {
+        assert_eq!(run(), 0);
+    }
+
+    #[test]
+    fn covers_every_feature_axis() {
+        let labels: Vec<String> = combinations().iter().map(label).collect();
+        for expected in [
+            "CPP c++20 executable ninja",
+            "C c11 executable ninja no-glob exit-code zig language-gitignore",
+            "C (no standard) executable vs excludes",
+            "CPP c++23 header-only ninja single-header language-gitignore",
+        ] {
+            assert!(
+                labels.iter().any(|label| label == expected),
+                "{} is not rendered",
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const CRATE_URL: &str = "https://crates.io/api/v1/crates/cppgen";
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Asks crates.io for the latest published cppgen and prints an upgrade hint
+/// when it is newer than this build. Checks at most once a day and never
+/// fails: any network or parsing problem is silently ignored.
+pub fn check_for_updates() {
+    let stamp = stamp_path();
+    if let Some(stamp) = &stamp {
+        if checked_recently(stamp) {
+            return;
+        }
+    }
+
+    let Some(latest) = latest_version() else {
+        return;
+    };
+
+    if let Some(stamp) = &stamp {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if let Some(parent) = stamp.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(stamp, now.to_string());
+    }
+
+    // On stderr, so it never ends up in a --stdout listing.
+    if let Some(hint) = upgrade_hint(&latest, env!("CARGO_PKG_VERSION")) {
+        eprintln!("{}", hint);
+    }
+}
+
+fn upgrade_hint(latest: &str, current: &str) -> Option<String> {
+    is_newer(latest, current).then(|| {
+        format!(
+            "A new cppgen version is available: v{} (current v{}). Run `cargo install cppgen` to upgrade.",
+            latest, current
+        )
+    })
+}
+
+fn latest_version() -> Option<String> {
+    let body = ureq::get(CRATE_URL)
+        .set("User-Agent", concat!("cppgen/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT)
+        .call()
+        .ok()?
+        .into_string()
+        .ok()?;
+    parse_latest_version(&body)
+}
+
+/// Reads the newest stable version from a crates.io `/crates/<name>` response.
+fn parse_latest_version(body: &str) -> Option<String> {
+    let response: serde_json::Value = serde_json::from_str(body).ok()?;
+    Some(
+        response["crate"]["max_stable_version"]
+            .as_str()?
+            .to_string(),
+    )
+}
+
+/// Whether the `major.minor.patch` version `latest` is greater than `current`.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Option<Vec<u64>> {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect()
+    };
+    match (parse(latest), parse(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+fn checked_recently(stamp: &Path) -> bool {
+    let Some(last) = std::fs::read_to_string(stamp)
+        .ok()
+        .and_then(|contents| contents.trim().parse::<u64>().ok())
+    else {
+        return false;
+    };
+    let last = UNIX_EPOCH + Duration::from_secs(last);
+    SystemTime::now()
+        .duration_since(last)
+        .is_ok_and(|elapsed| elapsed < CHECK_INTERVAL)
+}
+
+fn stamp_path() -> Option<PathBuf> {
+    let cache = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(cache.join("cppgen").join("last-update-check"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(version: &str) -> String {
+        format!(
+            r#"{{"crate": {{"name": "cppgen", "max_version": "9.0.0-beta", "max_stable_version": "{}"}}}}"#,
+            version
+        )
+    }
+
+    #[test]
+    fn hints_only_at_newer_versions() {
+        let newer = parse_latest_version(&response("1.2.0")).unwrap();
+        assert_eq!(newer, "1.2.0");
+        let hint = upgrade_hint(&newer, "1.0.1").unwrap();
+        assert!(hint.contains("v1.2.0 (current v1.0.1)"));
+
+        let same = parse_latest_version(&response("1.0.1")).unwrap();
+        assert_eq!(upgrade_hint(&same, "1.0.1"), None);
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer("1.10.0", "1.9.3"));
+        assert!(is_newer("v2.0.0", "1.99.99"));
+        assert!(!is_newer("1.0.1", "1.0.1"));
+        assert!(!is_newer("1.0.0", "1.0.1"));
+        assert!(!is_newer("not-a-version", "1.0.1"));
+    }
+
+    #[test]
+    fn ignores_unexpected_responses() {
+        assert_eq!(parse_latest_version("<html>rate limited</html>"), None);
+        assert_eq!(parse_latest_version(r#"{"errors": []}"#), None);
+    }
+}